@@ -0,0 +1,14 @@
+//! Interface of contracts that take a seat in a game and play it themselves.
+//! The game contract calls them through `advance_bot` whenever it is their turn.
+
+use crate::engine::{
+    Board,
+    Disk,
+};
+
+#[ink::trait_definition]
+pub trait ReversiBot {
+    // Square x,y to place a `disk` on. Returning a square where `disk` cannot be placed forfeits the game.
+    #[ink(message)]
+    fn choose_move(&self, board: Board, disk: Disk) -> (u8, u8);
+}
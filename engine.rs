@@ -0,0 +1,641 @@
+//! Reversi rules, independent of the contract and of the ink! environment.
+
+use ink::prelude::vec::Vec;
+
+pub const MAX_BOARD_SIZE: u8 = 10;
+pub const MIN_BOARD_SIZE: u8 = 6;
+
+// Directions to look for disks to flip, as (dx, dy).
+const DIRECTIONS: [(i32, i32); 8] = [(1, 0), (0, 1), (-1, 0), (0, -1), (1, 1), (-1, -1), (1, -1), (-1, 1)];
+
+#[derive(Clone, Copy, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum Disk {
+    Black,
+    White,
+}
+
+impl Disk {
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Black => Self::White,
+            Self::White => Self::Black,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum Error {
+    OutOfBounds,
+    SquareOccupied,
+    NoDisksFlipped,
+    // The player to move still has a valid place.
+    PassNotAllowed,
+    BoardTooSmall,
+    BoardTooBig,
+    OddBoardSize,
+}
+
+// Grid view of a board, indexed as `grid[y][x]`.
+pub type Grid = Vec<Vec<Option<Disk>>>;
+
+// One bit per square for each colour, square x,y at bit `y * size + x`.
+// 10x10 is the biggest board and needs 100 bits.
+#[derive(Clone, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Board {
+    size: u8,
+    black: u128,
+    white: u128,
+}
+
+impl Board {
+    pub fn new(size: u8) -> Result<Self, Error> {
+        if size < MIN_BOARD_SIZE {
+            return Err(Error::BoardTooSmall);
+        }
+        if size > MAX_BOARD_SIZE {
+            return Err(Error::BoardTooBig);
+        }
+        if !size.is_multiple_of(2) {
+            return Err(Error::OddBoardSize);
+        }
+
+        let mut board = Self { size, black: 0, white: 0 };
+        let half = size / 2;
+        board.set(half - 1, half - 1, Disk::White);
+        board.set(half, half, Disk::White);
+        board.set(half, half - 1, Disk::Black);
+        board.set(half - 1, half, Disk::Black);
+        Ok(board)
+    }
+
+    // Builds a board from its grid view. The grid has to be square.
+    pub fn from_grid(grid: Grid) -> Self {
+        let mut board = Self { size: grid.len() as u8, black: 0, white: 0 };
+        for (y, row) in grid.iter().enumerate() {
+            for (x, disk) in row.iter().enumerate() {
+                if let Some(disk) = disk {
+                    board.set(x as u8, y as u8, *disk);
+                }
+            }
+        }
+        board
+    }
+
+    pub fn to_grid(&self) -> Grid {
+        (0..self.size)
+            .map(|y| (0..self.size).map(|x| self.get(x, y)).collect())
+            .collect()
+    }
+
+    pub fn size(&self) -> u8 {
+        self.size
+    }
+
+    pub fn is_inside(&self, x: u8, y: u8) -> bool {
+        x < self.size && y < self.size
+    }
+
+    // Returns `None` for empty squares and squares outside of the board.
+    pub fn get(&self, x: u8, y: u8) -> Option<Disk> {
+        if !self.is_inside(x, y) {
+            return None;
+        }
+
+        let bit = self.bit(x, y);
+        if self.black & bit != 0 {
+            return Some(Disk::Black);
+        }
+        if self.white & bit != 0 {
+            return Some(Disk::White);
+        }
+        None
+    }
+
+    pub fn is_valid_place(&self, disk: Disk, x: u8, y: u8) -> bool {
+        self.check_place(disk, x, y).is_ok()
+    }
+
+    pub fn can_place_disk(&self, disk: Disk) -> bool {
+        self.legal_move_bits(disk) != 0
+    }
+
+    pub fn legal_moves(&self, disk: Disk) -> Vec<(u8, u8)> {
+        self.squares(self.legal_move_bits(disk))
+    }
+
+    // Returns the opponent disks that placing `disk` at x,y would flip.
+    // Empty if the move is not valid.
+    pub fn flippable_disks(&self, disk: Disk, x: u8, y: u8) -> Vec<(u8, u8)> {
+        if !self.is_valid_place(disk, x, y) {
+            return Vec::new();
+        }
+        self.squares(self.flip_bits(disk, x, y))
+    }
+
+    // Places `disk` at x,y and returns the number of opponent disks flipped.
+    // The board is left untouched if the move is not valid.
+    pub fn place_disk(&mut self, disk: Disk, x: u8, y: u8) -> Result<u8, Error> {
+        let flips = self.check_place(disk, x, y)?;
+        self.apply_bits(disk, self.bit(x, y) | flips);
+        Ok(flips.count_ones() as u8)
+    }
+
+    // Returns (black_count, white_count).
+    pub fn count_disks(&self) -> (u8, u8) {
+        (self.black.count_ones() as u8, self.white.count_ones() as u8)
+    }
+
+    // Returns the bits of the disks the move would flip.
+    fn check_place(&self, disk: Disk, x: u8, y: u8) -> Result<u128, Error> {
+        if !self.is_inside(x, y) {
+            return Err(Error::OutOfBounds);
+        }
+        if self.get(x, y).is_some() {
+            return Err(Error::SquareOccupied);
+        }
+
+        let flips = self.flip_bits(disk, x, y);
+        if flips == 0 {
+            return Err(Error::NoDisksFlipped);
+        }
+        Ok(flips)
+    }
+
+    fn bit(&self, x: u8, y: u8) -> u128 {
+        1 << (y as u32 * self.size as u32 + x as u32)
+    }
+
+    fn set(&mut self, x: u8, y: u8, disk: Disk) {
+        self.apply_bits(disk, self.bit(x, y));
+    }
+
+    fn disks(&self, disk: Disk) -> u128 {
+        match disk {
+            Disk::Black => self.black,
+            Disk::White => self.white,
+        }
+    }
+
+    fn apply_bits(&mut self, disk: Disk, bits: u128) {
+        match disk {
+            Disk::Black => {
+                self.black |= bits;
+                self.white &= !bits;
+            }
+            Disk::White => {
+                self.white |= bits;
+                self.black &= !bits;
+            }
+        }
+    }
+
+    // Bits of all squares on the board.
+    fn full_mask(&self) -> u128 {
+        let squares = self.size as u32 * self.size as u32;
+        (1 << squares) - 1
+    }
+
+    fn column_mask(&self, x: u8) -> u128 {
+        (0..self.size).fold(0, |mask, y| mask | self.bit(x, y))
+    }
+
+    // Moves every disk in `bits` one square in direction dx,dy.
+    // Disks leaving the board are dropped instead of wrapping to the next row.
+    fn shift(&self, bits: u128, dx: i32, dy: i32) -> u128 {
+        let offset = dy * self.size as i32 + dx;
+        let shifted = if offset >= 0 {
+            bits << offset
+        } else {
+            bits >> -offset
+        };
+
+        let mut mask = self.full_mask();
+        if dx > 0 {
+            mask &= !self.column_mask(0);
+        } else if dx < 0 {
+            mask &= !self.column_mask(self.size - 1);
+        }
+        shifted & mask
+    }
+
+    // Bits of every square where `disk` can be placed.
+    fn legal_move_bits(&self, disk: Disk) -> u128 {
+        let own = self.disks(disk);
+        let opponent = self.disks(disk.opposite());
+        let empty = self.full_mask() & !(own | opponent);
+
+        let mut moves = 0;
+        for (dx, dy) in DIRECTIONS {
+            // Opponent disks in a row starting next to one of our disks.
+            let mut candidates = self.shift(own, dx, dy) & opponent;
+            for _ in 0..self.size {
+                candidates |= self.shift(candidates, dx, dy) & opponent;
+            }
+            moves |= self.shift(candidates, dx, dy) & empty;
+        }
+        moves
+    }
+
+    // Bits of the opponent disks flipped by placing `disk` at x,y.
+    fn flip_bits(&self, disk: Disk, x: u8, y: u8) -> u128 {
+        let own = self.disks(disk);
+        let opponent = self.disks(disk.opposite());
+
+        let mut flips = 0;
+        for (dx, dy) in DIRECTIONS {
+            let mut line = 0;
+            let mut next = self.shift(self.bit(x, y), dx, dy);
+            while next & opponent != 0 {
+                line |= next;
+                next = self.shift(next, dx, dy);
+            }
+            // Flippable only if the row of opponent disks ends with our own disk.
+            if next & own != 0 {
+                flips |= line;
+            }
+        }
+        flips
+    }
+
+    // x,y coordinates of the set bits, row by row.
+    fn squares(&self, bits: u128) -> Vec<(u8, u8)> {
+        let mut squares = Vec::new();
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if bits & self.bit(x, y) != 0 {
+                    squares.push((x, y));
+                }
+            }
+        }
+        squares
+    }
+}
+
+// A board together with the colour to move. Black always moves first.
+#[derive(Clone, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Position {
+    board: Board,
+    to_move: Disk,
+}
+
+impl Position {
+    pub fn new(size: u8) -> Result<Self, Error> {
+        Ok(Self::with_board(Board::new(size)?, Disk::Black))
+    }
+
+    pub fn with_board(board: Board, to_move: Disk) -> Self {
+        Self { board, to_move }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn to_move(&self) -> Disk {
+        self.to_move
+    }
+
+    pub fn legal_moves(&self) -> Vec<(u8, u8)> {
+        self.board.legal_moves(self.to_move)
+    }
+
+    // Plays x,y for the colour to move and hands the turn to the opponent.
+    // Returns the number of disks flipped.
+    pub fn play(&mut self, x: u8, y: u8) -> Result<u8, Error> {
+        let flipped = self.board.place_disk(self.to_move, x, y)?;
+        self.to_move = self.to_move.opposite();
+        Ok(flipped)
+    }
+
+    // Hands the turn to the opponent. Only allowed without any valid place.
+    pub fn pass(&mut self) -> Result<(), Error> {
+        if self.board.can_place_disk(self.to_move) {
+            return Err(Error::PassNotAllowed);
+        }
+        self.to_move = self.to_move.opposite();
+        Ok(())
+    }
+
+    // Neither colour has a valid place left.
+    pub fn is_terminal(&self) -> bool {
+        !self.board.can_place_disk(Disk::Black) && !self.board.can_place_disk(Disk::White)
+    }
+
+    // Returns (black_count, white_count).
+    pub fn score(&self) -> (u8, u8) {
+        self.board.count_disks()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_valid_place_ok() {
+        let board = Board::new(6).unwrap();
+
+        //    0  1  2  3  4  5
+        // 0
+        // 1
+        // 2 　　　　⚪️ ⚫️
+        // 3        ⚫️ ⚪️
+        // 4
+        // 5
+        //
+
+        // White
+        assert!(board.is_valid_place(Disk::White, 3, 1));
+        assert!(board.is_valid_place(Disk::White, 4, 2));
+        assert!(board.is_valid_place(Disk::White, 1, 3));
+        assert!(board.is_valid_place(Disk::White, 2, 4));
+        assert!(!board.is_valid_place(Disk::White, 2, 1));
+        assert!(!board.is_valid_place(Disk::White, 2, 2));
+        assert!(!board.is_valid_place(Disk::White, 3, 2));
+        assert!(!board.is_valid_place(Disk::White, 0, 0));
+        assert!(!board.is_valid_place(Disk::White, 5, 6));
+
+        // Black
+        assert!(board.is_valid_place(Disk::Black, 2, 1));
+        assert!(board.is_valid_place(Disk::Black, 4, 3));
+        assert!(board.is_valid_place(Disk::Black, 3, 4));
+        assert!(board.is_valid_place(Disk::Black, 1, 2));
+        assert!(!board.is_valid_place(Disk::Black, 3, 1));
+        assert!(!board.is_valid_place(Disk::Black, 2, 2));
+        assert!(!board.is_valid_place(Disk::Black, 3, 2));
+        assert!(!board.is_valid_place(Disk::Black, 0, 0));
+        assert!(!board.is_valid_place(Disk::Black, 6, 5));
+    }
+
+    #[test]
+    fn is_valid_place_ok_2() {
+        let board = Board::from_grid(vec![
+            vec![None; 6],
+            vec![None, None, None, Some(Disk::White), None, None],
+            vec![None, None, Some(Disk::Black), Some(Disk::White), Some(Disk::Black), None],
+            vec![None, None, Some(Disk::Black), Some(Disk::White), None, None],
+            vec![None; 6],
+            vec![None; 6],
+        ]);
+
+        //    0  1  2  3  4  5
+        // 0
+        // 1          ⚪️
+        // 2 　　　　⚫️ ⚪️ ⚫ ️ 
+        // 3        ⚫️ ⚪️
+        // 4
+        // 5
+        //
+
+        // White
+        assert!(board.is_valid_place(Disk::White, 1, 2));
+        assert!(board.is_valid_place(Disk::White, 1, 3));
+        assert!(board.is_valid_place(Disk::White, 1, 4));
+        assert!(board.is_valid_place(Disk::White, 5, 2));
+        assert!(board.is_valid_place(Disk::White, 5, 3));
+        assert!(!board.is_valid_place(Disk::White, 2, 1));
+        assert!(!board.is_valid_place(Disk::White, 2, 2));
+        assert!(!board.is_valid_place(Disk::White, 3, 2));
+        assert!(!board.is_valid_place(Disk::White, 0, 0));
+        assert!(!board.is_valid_place(Disk::White, 5, 6));
+
+        assert!(board.is_valid_place(Disk::Black, 2, 0));
+        assert!(board.is_valid_place(Disk::Black, 4, 0));
+        assert!(board.is_valid_place(Disk::Black, 4, 4));
+        assert!(!board.is_valid_place(Disk::Black, 2, 1));
+        assert!(!board.is_valid_place(Disk::Black, 3, 0));
+        assert!(!board.is_valid_place(Disk::Black, 3, 4));
+    }
+
+    #[test]
+    fn count_disks_ok() {
+        let board = Board::new(6).unwrap();
+
+        let (white_count, black_count) = board.count_disks();
+        assert_eq!(white_count, 2);
+        assert_eq!(black_count, 2);
+
+        let board = Board::from_grid(vec![
+            vec![None; 6],
+            vec![None, None, None, Some(Disk::White), None, None],
+            vec![None, None, Some(Disk::Black), Some(Disk::White), Some(Disk::Black), None],
+            vec![None, None, Some(Disk::Black), Some(Disk::White), None, None],
+            vec![None; 6],
+            vec![None; 6],
+        ]);
+
+        //    0  1  2  3  4  5
+        // 0
+        // 1          ⚪️
+        // 2 　　　　⚫️ ⚪️ ⚫ ️ 
+        // 3        ⚫️ ⚪️
+        // 4
+        // 5
+        //
+
+        let (white_count, black_count) = board.count_disks();
+        assert_eq!(white_count, 3);
+        assert_eq!(black_count, 3);
+
+        let board = Board::from_grid(vec![
+            vec![None, None, None, None, Some(Disk::Black), None],
+            vec![None, None, None, Some(Disk::Black), None, None],
+            vec![None, Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White)],
+            vec![None, Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), None, None],
+            vec![None, Some(Disk::White), None, Some(Disk::Black), None, None],
+            vec![None; 6],
+        ]);
+
+        //    0  1  2  3  4  5
+        // 0             ⚫
+        // 1          ⚫
+        // 2 　　 ⚪ ⚪ ⚪ ⚪️ ️⚪️️
+        // 3      ⚫ ⚫ ⚫
+        // 4      ⚪️️   ⚫
+        // 5
+        //
+
+        let (white_count, black_count) = board.count_disks();
+        assert_eq!(white_count, 6);
+        assert_eq!(black_count, 6);
+    }
+
+    #[test]
+    fn can_place_disk_ok() {
+
+        let board = Board::from_grid(vec![
+            vec![None, None, None, None, Some(Disk::Black), None],
+            vec![None, None, None, Some(Disk::Black), None, None],
+            vec![None, Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White)],
+            vec![None, Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), None, None],
+            vec![None, Some(Disk::White), None, Some(Disk::Black), None, None],
+            vec![None; 6],
+        ]);
+        //    0  1  2  3  4  5
+        // 0             ⚫
+        // 1          ⚫
+        // 2 　　 ⚪ ⚪ ⚪ ⚪️ ️⚪️️
+        // 3      ⚫ ⚫ ⚫
+        // 4      ⚪️️   ⚫
+        // 5
+        //
+
+        assert!(board.can_place_disk(Disk::White));
+        assert!(board.can_place_disk(Disk::Black));
+
+        let board = Board::from_grid(vec![
+            vec![None; 6],
+            vec![None, None, None, Some(Disk::White), None, None],
+            vec![None, None, Some(Disk::Black), Some(Disk::White), Some(Disk::Black), None],
+            vec![None, None, Some(Disk::Black), Some(Disk::White), None, None],
+            vec![None; 6],
+            vec![None; 6],
+        ]);
+
+        //    0  1  2  3  4  5
+        // 0
+        // 1          ⚪️
+        // 2 　　　　⚫️ ⚪️ ⚫ ️ 
+        // 3        ⚫️ ⚪️
+        // 4
+        // 5
+        //
+
+        assert!(board.can_place_disk(Disk::White));
+        assert!(board.can_place_disk(Disk::Black));
+
+        let board = Board::from_grid(vec![
+            vec![Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White)],
+            vec![Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White)],
+            vec![Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White)],
+            vec![Some(Disk::Black), Some(Disk::Black), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White)],
+            vec![None, Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black)],
+            vec![Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White)],
+        ]);
+        //    0  1  2  3  4  5
+        // 0  ⚪ ⚪ ⚪ ⚪ ⚪️️️️  ⚪️️
+        // 1  ⚪ ⚪ ⚪ ⚪ ⚪️️ ⚪
+        // 2　⚪ ⚪ ⚪ ⚪ ⚪️ ️⚪️️
+        // 3  ⚫ ⚫  ⚪ ⚪ ⚪ ⚪
+        // 4     ⚫  ⚫ ⚫ ⚫ ⚫
+        // 5  ⚪ ⚪  ⚪  ⚪ ⚪ ⚪
+        //
+
+        assert!(board.can_place_disk(Disk::White));
+        assert!(!board.can_place_disk(Disk::Black));
+    }
+
+    #[test]
+    fn flips_do_not_wrap_around_edges() {
+        let board = Board::from_grid(vec![
+            vec![None, None, None, None, Some(Disk::Black), Some(Disk::White)],
+            vec![None; 6],
+            vec![None; 6],
+            vec![None; 6],
+            vec![None; 6],
+            vec![Some(Disk::White), Some(Disk::Black), None, None, None, None],
+        ]);
+
+        //    0  1  2  3  4  5
+        // 0              ⚫ ⚪
+        // 1
+        // 2
+        // 3
+        // 4
+        // 5  ⚪ ⚫
+        //
+
+        // (5,0) and (0,1) are neighbours in the bitboard but not on the board.
+        assert!(!board.is_valid_place(Disk::Black, 0, 1));
+        assert!(!board.is_valid_place(Disk::White, 5, 4));
+        assert!(!board.can_place_disk(Disk::Black));
+        assert!(board.can_place_disk(Disk::White));
+        assert_eq!(board.legal_moves(Disk::White), vec![(3, 0), (2, 5)]);
+    }
+
+    #[test]
+    fn board_size_is_checked() {
+        assert_eq!(Board::new(4), Err(Error::BoardTooSmall));
+        assert_eq!(Board::new(12), Err(Error::BoardTooBig));
+        assert_eq!(Board::new(7), Err(Error::OddBoardSize));
+        assert!(Board::new(6).is_ok());
+        assert!(Board::new(8).is_ok());
+        assert!(Board::new(10).is_ok());
+    }
+
+    #[test]
+    fn place_disk_fail() {
+        let mut board = Board::new(10).unwrap();
+        let initial = board.clone();
+
+        assert_eq!(board.place_disk(Disk::Black, 10, 0), Err(Error::OutOfBounds));
+        assert_eq!(board.place_disk(Disk::Black, 0, 10), Err(Error::OutOfBounds));
+        assert_eq!(board.place_disk(Disk::Black, 255, 255), Err(Error::OutOfBounds));
+        assert_eq!(board.place_disk(Disk::Black, 4, 4), Err(Error::SquareOccupied));
+        assert_eq!(board.place_disk(Disk::Black, 0, 0), Err(Error::NoDisksFlipped));
+        assert_eq!(board.place_disk(Disk::Black, 3, 3), Err(Error::NoDisksFlipped));
+        assert_eq!(board, initial);
+
+        assert_eq!(board.place_disk(Disk::Black, 4, 3), Ok(1));
+        assert_ne!(board, initial);
+    }
+
+    #[test]
+    fn position_play_and_pass_ok() {
+        let mut position = Position::new(6).unwrap();
+        assert_eq!(position.to_move(), Disk::Black);
+        assert_eq!(position.legal_moves(), vec![(2, 1), (1, 2), (4, 3), (3, 4)]);
+        assert_eq!(position.pass(), Err(Error::PassNotAllowed));
+        assert_eq!(position.play(0, 0), Err(Error::NoDisksFlipped));
+        assert_eq!(position.play(6, 0), Err(Error::OutOfBounds));
+        assert_eq!(position.play(2, 2), Err(Error::SquareOccupied));
+
+        assert_eq!(position.play(2, 1), Ok(1));
+        assert_eq!(position.to_move(), Disk::White);
+        assert_eq!(position.score(), (4, 1));
+        assert!(!position.is_terminal());
+
+        let mut position = Position::with_board(
+            Board::from_grid(vec![
+                vec![None, Some(Disk::White), Some(Disk::Black), None, None, None],
+                vec![None; 6],
+                vec![None; 6],
+                vec![None; 6],
+                vec![None; 6],
+                vec![Some(Disk::Black), Some(Disk::White), None, None, None, None],
+            ]),
+            Disk::Black,
+        );
+
+        //    0  1  2  3  4  5
+        // 0     ⚪ ⚫
+        // 1
+        // 2
+        // 3
+        // 4
+        // 5  ⚫ ⚪
+        //
+
+        assert_eq!(position.play(0, 0), Ok(1));
+        assert!(position.legal_moves().is_empty());
+        assert!(!position.is_terminal());
+        assert_eq!(position.pass(), Ok(()));
+        assert_eq!(position.to_move(), Disk::Black);
+        assert_eq!(position.play(2, 5), Ok(1));
+        assert!(position.is_terminal());
+        assert_eq!(position.score(), (6, 0));
+    }
+}
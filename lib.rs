@@ -1,738 +1,3757 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
-#[ink::contract]
+pub mod bot;
+pub mod engine;
+pub mod randomness;
+pub mod rating;
+pub mod tournament;
+
+#[ink::contract(env = crate::randomness::ReversiEnvironment)]
 mod reversi {
     use ink::{
-        prelude::{
-            vec,
-            vec::Vec
+        codegen::TraitCallBuilder,
+        env::hash::{
+            Blake2x256,
+            HashOutput,
         },
+        prelude::vec::Vec,
+        storage::Mapping,
+    };
+
+    use crate::bot::ReversiBot;
+    use crate::engine::{
+        self,
+        Board,
+        Disk,
+        Grid,
+        Position,
+    };
+    use crate::randomness::RandomnessError;
+    use crate::rating::{
+        self,
+        Rating,
     };
+    use crate::tournament::{
+        self,
+        Bracket,
+        Format,
+        Pairing,
+        Standing,
+        Table,
+    };
+
+    pub type GameId = u32;
+    pub type TournamentId = u32;
+    // Answer of a bot to `ReversiBot::choose_move`, or why the call failed.
+    type BotReply = Result<ink::MessageResult<(u8, u8)>, ink::env::Error>;
+
+    #[derive(Clone, Debug, scale::Decode, scale::Encode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum ReversiError {
+        InvalidPlayer,
+        OutOfBounds,
+        SquareOccupied,
+        NoDisksFlipped,
+        GameIsOver,
+        GameIsNotOver,
+        GameNotFound,
+        GameNotStarted,
+        GameAlreadyJoined,
+        IncorrectStake,
+        TransferFailed,
+        NoMoveTimeout,
+        DeadlineNotReached,
+        DrawAlreadyOffered,
+        NoDrawOffer,
+        InvalidPly,
+        CannotPass,
+        BoardTooSmall,
+        BoardTooBig,
+        OddBoardSize,
+        SamePlayer,
+        ChallengeNotFound,
+        ChallengeExpired,
+        ChallengeNotExpired,
+        NotInvited,
+        TournamentNotFound,
+        TournamentAlreadyStarted,
+        TournamentFull,
+        AlreadyRegistered,
+        // The seeds are not an order of the registered players.
+        InvalidSeeds,
+        NotEnoughPlayers,
+        NoRounds,
+        TooManyRounds,
+        InvalidPrizeShares,
+        NoPrize,
+        NoColourDraw,
+        AlreadyCommitted,
+        // The commitment is the same as the opponent's.
+        DuplicateCommitment,
+        // Secrets can only be revealed once both players have committed.
+        CommitmentMissing,
+        AlreadyRevealed,
+        // The secret does not match the commitment.
+        InvalidSecret,
+        OpeningTooLong,
+        // The chain's random source failed.
+        RandomnessUnavailable,
+        // A channel move is not signed by the player who had to make it.
+        InvalidSignature,
+        DisputeAlreadyStarted,
+        // A relayed move does not carry the signer's next nonce.
+        InvalidNonce,
+        SessionKeyExpired,
+        SessionKeyNotFound,
+        // The key is registered by the other player of the game.
+        SessionKeyInUse,
+        // The bond of a result claim is below the game's stake, or zero.
+        InsufficientBond,
+        // The claimed board is not a finished game of this size, or does not match the winner.
+        InvalidClaim,
+        ResultAlreadyClaimed,
+        NoResultClaim,
+        // No move can be played, and no timeout claimed, while a result claim is pending.
+        ResultClaimPending,
+        // The moves lead to the claimed result.
+        ClaimIsValid,
+        // The moves do not finish the game, so they cannot prove a claim wrong.
+        TranscriptNotFinished,
+        // Bots cannot take part in colour draws.
+        BotNotSupported,
+        NotBotTurn,
+        // Only contracts can hold a bot seat.
+        NotAContract,
+    }
+
+    impl From<engine::Error> for ReversiError {
+        fn from(error: engine::Error) -> Self {
+            match error {
+                engine::Error::OutOfBounds => Self::OutOfBounds,
+                engine::Error::SquareOccupied => Self::SquareOccupied,
+                engine::Error::NoDisksFlipped => Self::NoDisksFlipped,
+                engine::Error::PassNotAllowed => Self::CannotPass,
+                engine::Error::BoardTooSmall => Self::BoardTooSmall,
+                engine::Error::BoardTooBig => Self::BoardTooBig,
+                engine::Error::OddBoardSize => Self::OddBoardSize,
+            }
+        }
+    }
+
+    impl From<RandomnessError> for ReversiError {
+        fn from(_: RandomnessError) -> Self {
+            Self::RandomnessUnavailable
+        }
+    }
+
+    #[ink(event)]
+    pub struct GameCreated {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        player_1: AccountId,
+        #[ink(topic)]
+        player_2: AccountId,
+        board_size: u8,
+    }
+
+    #[ink(event)]
+    pub struct ChallengeOpened {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        creator: AccountId,
+        board_size: u8,
+        stake: Balance,
+        expires_at: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct ChallengeCancelled {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        creator: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct GameCancelled {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        creator: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct GameJoined {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        player: AccountId,
+        stake: Balance,
+    }
+
+    #[ink(event)]
+    pub struct MovePlayed {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        player: AccountId,
+        x: u8,
+        y: u8,
+        flipped: u8,
+    }
+
+    // Emitted when `player` has no valid place and the turn goes back to the opponent.
+    #[ink(event)]
+    pub struct TurnPassed {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        player: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ColourCommitted {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        player: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ColourRevealed {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        player: AccountId,
+        secret: [u8; 32],
+    }
+
+    #[ink(event)]
+    pub struct ColoursAssigned {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        black: AccountId,
+        #[ink(topic)]
+        white: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ChannelSettled {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        player: AccountId,
+        move_count: u32,
+    }
+
+    #[ink(event)]
+    pub struct DisputeStarted {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        player: AccountId,
+        deadline: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct SessionKeyRegistered {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        player: AccountId,
+        #[ink(topic)]
+        key: AccountId,
+        expires_at: BlockNumber,
+    }
+
+    #[ink(event)]
+    pub struct SessionKeyRevoked {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        player: AccountId,
+        #[ink(topic)]
+        key: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ResultClaimed {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        claimant: AccountId,
+        winner: Option<AccountId>,
+        bond: Balance,
+        deadline: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct FraudProven {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        claimant: AccountId,
+        #[ink(topic)]
+        challenger: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct BotSeatChanged {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        player: AccountId,
+        is_bot: bool,
+    }
+
+    #[ink(event)]
+    pub struct PlayerResigned {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        player: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct DrawOffered {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        player: AccountId,
+    }
 
-    const ZERO_ADDRESS: [u8; 32] = [0; 32];
+    #[ink(event)]
+    pub struct DrawDeclined {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        player: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct TournamentCreated {
+        #[ink(topic)]
+        tournament_id: TournamentId,
+        #[ink(topic)]
+        organiser: AccountId,
+        format: Format,
+        board_size: u8,
+    }
+
+    #[ink(event)]
+    pub struct PlayerRegistered {
+        #[ink(topic)]
+        tournament_id: TournamentId,
+        #[ink(topic)]
+        player: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct RoundStarted {
+        #[ink(topic)]
+        tournament_id: TournamentId,
+        round: u32,
+        bye: Option<AccountId>,
+    }
 
-    const MAX_BOARD_SIZE: u8 = 10;
-    const MIN_BOARD_SIZE: u8 = 6;
+    #[ink(event)]
+    pub struct TournamentCancelled {
+        #[ink(topic)]
+        tournament_id: TournamentId,
+        #[ink(topic)]
+        organiser: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct TournamentFinished {
+        #[ink(topic)]
+        tournament_id: TournamentId,
+        #[ink(topic)]
+        winner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct GameEnded {
+        #[ink(topic)]
+        game_id: GameId,
+        #[ink(topic)]
+        winner: Option<AccountId>,
+        black_count: u8,
+        white_count: u8,
+    }
+
+    // Entry of a game's move history. Black always plays the first move.
+    #[derive(Clone, Copy, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Move {
+        Place { x: u8, y: u8 },
+        // The player to move had no valid place and the turn went back to the opponent.
+        Pass,
+    }
 
+    // Final result of a game. Scores are `(black_count, white_count)`.
     #[derive(Clone, Copy, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
     #[cfg_attr(
         feature = "std",
         derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
     )]
-    pub enum Disk {
-        Black,
-        White,
+    pub enum GameOutcome {
+        InProgress,
+        Won {
+            winner: AccountId,
+            loser: AccountId,
+            score: (u8, u8),
+        },
+        Draw {
+            score: (u8, u8),
+        },
+        Resigned {
+            winner: AccountId,
+            loser: AccountId,
+        },
+        TimedOut {
+            winner: AccountId,
+            loser: AccountId,
+        },
+        // The loser is a bot that chose an invalid place or failed to answer.
+        Forfeited {
+            winner: AccountId,
+            loser: AccountId,
+        },
     }
 
-    impl Disk {
-        fn opposite(self) -> Self {
+    impl GameOutcome {
+        fn winner(&self) -> Option<AccountId> {
             match self {
-                Self::Black => Self::White,
-                Self::White => Self::Black,
+                Self::Won { winner, .. }
+                | Self::Resigned { winner, .. }
+                | Self::TimedOut { winner, .. }
+                | Self::Forfeited { winner, .. } => Some(*winner),
+                Self::InProgress | Self::Draw { .. } => None,
+            }
+        }
+    }
+
+    // Settings chosen by the creator of a game.
+    #[derive(Clone, Copy, Debug, Default, scale::Decode, scale::Encode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct GameOptions {
+        // Time the active player has for a move before the opponent can claim the game.
+        pub move_timeout: Option<Timestamp>,
+        // Whether the result updates the players' ratings.
+        pub rated: bool,
+        pub colours: ColourAssignment,
+        // Number of random moves played from the start position before the players
+        // take over, at most `MAX_RANDOM_OPENING`. They are part of the move history.
+        pub random_opening: u8,
+    }
+
+    pub const MAX_RANDOM_OPENING: u8 = 8;
+
+    impl GameOptions {
+        fn check(&self) -> Result<(), ReversiError> {
+            if self.random_opening > MAX_RANDOM_OPENING {
+                return Err(ReversiError::OpeningTooLong)
+            }
+            // Without a timeout, a player who never reveals would block the game.
+            if self.colours == ColourAssignment::CommitReveal && self.move_timeout.is_none() {
+                return Err(ReversiError::NoMoveTimeout)
+            }
+            Ok(())
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, Default, scale::Decode, scale::Encode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum ColourAssignment {
+        // The creator of the game plays Black.
+        #[default]
+        Fixed,
+        // Drawn with `commit_colour` and `reveal_colour` before the first move.
+        // Needs a `move_timeout`, after which a player who did not reveal loses.
+        CommitReveal,
+        // Drawn from the chain's random source when the game starts.
+        Random,
+    }
+
+    // Time the active player has to answer `start_dispute` with a move on-chain.
+    pub const DISPUTE_PERIOD: Timestamp = 24 * 60 * 60 * 1000;
+
+    // Move exchanged off-chain between the players of a game and submitted with `settle_channel`.
+    #[derive(Clone, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct SignedMove {
+        pub x: u8,
+        pub y: u8,
+        // ECDSA signature of `channel_message` by the player who made the move.
+        pub signature: [u8; 65],
+    }
+
+    // Time during which a result claim can be answered with `prove_fraud`.
+    pub const CLAIM_PERIOD: Timestamp = 24 * 60 * 60 * 1000;
+
+    // Final position of a game played off-chain, posted with a bond instead of the moves.
+    #[derive(Clone, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ResultClaim {
+        claimant: AccountId,
+        board: Board,
+        winner: Option<AccountId>,
+        bond: Balance,
+        deadline: Timestamp,
+    }
+
+    // Account allowed to move for `player` in a single game. It cannot resign, claim or withdraw.
+    #[derive(Clone, Copy, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct SessionKey {
+        player: AccountId,
+        // First block where the key is no longer valid.
+        expires_at: BlockNumber,
+    }
+
+    // Commit-reveal state of a colour draw, indexed like `Game::players`.
+    #[derive(Clone, Debug, Default, scale::Decode, scale::Encode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ColourDraw {
+        commitments: [Option<Hash>; 2],
+        secrets: [Option<[u8; 32]>; 2],
+    }
+
+    impl ColourDraw {
+        fn commitment(secret: &[u8; 32]) -> Hash {
+            let mut output = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_bytes::<Blake2x256>(secret, &mut output);
+            Hash::from(output)
+        }
+
+        // 0 before committing, 1 once committed and 2 once revealed.
+        fn progress(&self, index: usize) -> u8 {
+            match (self.commitments[index], self.secrets[index]) {
+                (_, Some(_)) => 2,
+                (Some(_), None) => 1,
+                (None, None) => 0,
             }
         }
     }
 
+    // A single match between two players, stored in the contract by `GameId`.
+    // player_1 plays Black and player_2 White, so the colour to move picks the active player.
     #[derive(Clone, Debug, scale::Decode, scale::Encode)]
     #[cfg_attr(
         feature = "std",
         derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
     )]
-    pub struct Board {
-        // player_1: Black, player_2: White
-        disks: Vec<Vec<Option<Disk>>>,
+    pub struct Game {
+        players: [AccountId; 2],
+        position: Position,
+        outcome: GameOutcome,
+        // Amount deposited by each player. The winner takes both deposits.
+        stake: Balance,
+        // Whether player_2 has accepted the game and matched the stake. Only the games
+        // of the constructor and of tournaments start without it.
+        is_joined: bool,
+        options: GameOptions,
+        last_move_at: Timestamp,
+        // Player who offered a draw that the opponent has not answered yet.
+        draw_offer: Option<AccountId>,
+        // Pending colour draw. No move can be played until it is over.
+        colour_draw: Option<ColourDraw>,
+        // Time by which the active player has to move on-chain after `start_dispute`.
+        dispute_deadline: Option<Timestamp>,
+        // Seats of the players held by contracts implementing `ReversiBot`, declared with `set_bot_seat`.
+        bots: [bool; 2],
     }
 
-    impl Board {
-        fn new(size: u8) -> Self {
-            assert!(size >= MIN_BOARD_SIZE, "Board size is too small");
-            assert!(size <= MAX_BOARD_SIZE, "Board size is too big");
-            assert!(size % 2 == 0, "Board size should be even number");
+    impl Game {
+        fn new(
+            board_size: u8,
+            player_1: AccountId,
+            player_2: AccountId,
+            stake: Balance,
+            options: GameOptions,
+        ) -> Result<Self, ReversiError> {
+            if player_1 == player_2 {
+                return Err(ReversiError::SamePlayer)
+            }
+            options.check()?;
+
+            Ok(Self {
+                players: [player_1, player_2],
+                // Black moves first, and player_1 plays it unless the colours are drawn.
+                position: Position::new(board_size)?,
+                outcome: GameOutcome::InProgress,
+                stake,
+                is_joined: false,
+                options,
+                last_move_at: 0,
+                draw_offer: None,
+                colour_draw: (options.colours == ColourAssignment::CommitReveal).then(ColourDraw::default),
+                dispute_deadline: None,
+                bots: [false; 2],
+            })
+        }
+
+        fn board(&self) -> &Board {
+            self.position.board()
+        }
+
+        fn is_game_over(&self) -> bool {
+            self.outcome != GameOutcome::InProgress
+        }
+
+        // The earlier of the move timeout and of the dispute deadline.
+        fn deadline(&self) -> Option<Timestamp> {
+            let move_deadline = self
+                .options
+                .move_timeout
+                .map(|move_timeout| self.last_move_at.saturating_add(move_timeout));
+            move_deadline.into_iter().chain(self.dispute_deadline).min()
+        }
+
+        // Swaps the players along with their bot seats.
+        fn swap_colours(&mut self) {
+            self.players.swap(0, 1);
+            self.bots.swap(0, 1);
+        }
+
+        fn get_active_player(&self) -> AccountId {
+            match self.position.to_move() {
+                Disk::Black => self.players[0],
+                Disk::White => self.players[1],
+            }
+        }
+
+        fn is_active(&self, player: AccountId) -> bool {
+            self.get_active_player() == player
+        }
 
-            let size = size as usize;
-            let mut disks = vec![vec![None; size]; size];
+        // Returns `None` if `player` does not play in this game.
+        fn player_index(&self, player: AccountId) -> Option<usize> {
+            self.players.iter().position(|p| *p == player)
+        }
+
+        fn get_opponent(&self, player: AccountId) -> Option<AccountId> {
+            match self.players {
+                [player_1, player_2] if player_1 == player => Some(player_2),
+                [player_1, player_2] if player_2 == player => Some(player_1),
+                _ => None,
+            }
+        }
 
-            disks[size/2-1][size/2-1] = Some(Disk::White);
-            disks[size/2][size/2] = Some(Disk::White);
-            disks[size/2-1][size/2] = Some(Disk::Black);
-            disks[size/2][size/2-1] = Some(Disk::Black);
-            Self { disks }
+        fn count_disks(&self) -> (u8, u8) {
+            self.position.score()
         }
     }
 
+
+    // Open game waiting for an opponent. It shares its id with the game started by `join_game`.
     #[derive(Clone, Debug, scale::Decode, scale::Encode)]
     #[cfg_attr(
         feature = "std",
         derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
     )]
-    pub enum ReversiError {
-        InvalidPlayer,
-        CannotPlaceDisk,
-        GameIsOver,
-        GameIsNotOver,
+    pub struct Challenge {
+        creator: AccountId,
+        board_size: u8,
+        stake: Balance,
+        options: GameOptions,
+        // Accounts invited to join. Anyone can join if `None`.
+        allow_list: Option<Vec<AccountId>>,
+        // After this time nobody can join, and the creator can cancel and get the stake back.
+        expires_at: Timestamp,
+    }
+
+    // Record of an account over all the games it finished.
+    #[derive(Clone, Copy, Debug, Default, scale::Decode, scale::Encode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PlayerStats {
+        wins: u32,
+        losses: u32,
+        draws: u32,
+        games_played: u32,
+        // Sum of own disks minus the opponent's disks at the end of each game.
+        disk_differential: i32,
+        // Most own disks at the end of a game.
+        best_score: u8,
+    }
+
+    #[derive(Clone, Copy, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum LeaderboardOrder {
+        Rating,
+        Wins,
+    }
+
+    #[derive(Clone, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct LeaderboardEntry {
+        account: AccountId,
+        rating: Rating,
+        stats: PlayerStats,
+    }
+
+    // Players register until the organiser starts the tournament. The games of a round are
+    // created at once, and the next round starts when the last of them ends.
+    #[derive(Clone, Debug, scale::Decode, scale::Encode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Tournament {
+        organiser: AccountId,
+        format: Format,
+        board_size: u8,
+        // Settings of every game. Tournament games have no stake.
+        options: GameOptions,
+        // Registered players. The tournament refers to them by their index.
+        players: Vec<AccountId>,
+        table: Table,
+        // Only used by knockout tournaments.
+        bracket: Bracket,
+        // Round being played, counted from 1. 0 until the tournament starts.
+        round: u32,
+        round_count: u32,
+        games: Vec<GameId>,
+        // Games of the current round that have not ended yet.
+        pending_games: u32,
+        is_finished: bool,
+        // Escrowed by the organiser. When the tournament finishes, the prizes
+        // are credited to the winners, who collect them with `withdraw_prize`.
+        prize_pool: Balance,
+        // Percent of the pool for each place, from first place down.
+        prize_shares: Vec<u32>,
+    }
+
+    impl Tournament {
+        // Players grouped by finishing place, from first place down.
+        fn places(&self) -> Vec<Vec<u32>> {
+            match self.format {
+                Format::Knockout { .. } => self.bracket.places(),
+                Format::RoundRobin | Format::Swiss { .. } => self
+                    .table
+                    .standings()
+                    .into_iter()
+                    .map(|standing| Vec::from([standing.player]))
+                    .collect(),
+            }
+        }
+    }
+
+    // Tournament a game belongs to, and the players' indices in it.
+    #[derive(Clone, Copy, Debug, scale::Decode, scale::Encode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    struct TournamentGame {
+        tournament_id: TournamentId,
+        pairing: Pairing,
     }
 
     #[ink(storage)]
     pub struct Reversi {
-        board_size: u8,
-        players: [AccountId; 2],
-        active_player_index: u8,
-        board: Board,
-        is_game_over: bool,
-        winner: AccountId,
+        games: Mapping<GameId, Game>,
+        challenges: Mapping<GameId, Challenge>,
+        moves: Mapping<GameId, Vec<Move>>,
+        next_game_id: GameId,
+        ratings: Mapping<AccountId, Rating>,
+        stats: Mapping<AccountId, PlayerStats>,
+        // Every account that finished a game, indexed in order of its first game.
+        ranked_players: Mapping<u32, AccountId>,
+        ranked_player_count: u32,
+        tournaments: Mapping<TournamentId, Tournament>,
+        tournament_games: Mapping<GameId, TournamentGame>,
+        next_tournament_id: TournamentId,
+        // Prizes won in tournaments and not withdrawn yet.
+        prizes: Mapping<AccountId, Balance>,
+        // Number of relayed moves signed by each account.
+        nonces: Mapping<AccountId, u64>,
+        session_keys: Mapping<(GameId, AccountId), SessionKey>,
+        result_claims: Mapping<GameId, ResultClaim>,
     }
 
     impl Reversi {
         #[ink(constructor)]
-        pub fn new(board_size: u8, player_1: AccountId, player_2: AccountId) -> Self {
-            assert!(player_1 != player_2, "palyer_1 and player_2 should be different account");
+        pub fn new(board_size: u8, player_1: AccountId, player_2: AccountId) -> Result<Self, ReversiError> {
+            let mut reversi = Self {
+                games: Mapping::default(),
+                challenges: Mapping::default(),
+                moves: Mapping::default(),
+                next_game_id: 0,
+                ratings: Mapping::default(),
+                stats: Mapping::default(),
+                ranked_players: Mapping::default(),
+                ranked_player_count: 0,
+                tournaments: Mapping::default(),
+                tournament_games: Mapping::default(),
+                next_tournament_id: 0,
+                prizes: Mapping::default(),
+                nonces: Mapping::default(),
+                session_keys: Mapping::default(),
+                result_claims: Mapping::default(),
+            };
+            let mut game = Game::new(board_size, player_1, player_2, 0, GameOptions::default())?;
+            game.is_joined = true;
+            reversi.insert_game(game)?;
+            Ok(reversi)
+        }
+
+        // The transferred value is escrowed as the caller's stake. The opponent has to accept
+        // the game with `join_game`, matching the stake, before it starts, even without a stake.
+        // With `move_timeout` set, a player who does not move in time loses on `claim_timeout`.
+        #[ink(message, payable)]
+        pub fn create_game(
+            &mut self,
+            board_size: u8,
+            opponent: AccountId,
+            options: GameOptions,
+        ) -> Result<GameId, ReversiError> {
+            let player = Self::env().caller();
+            let stake = Self::env().transferred_value();
+            let game = Game::new(board_size, player, opponent, stake, options)?;
+            self.insert_game(game)
+        }
+
+        // Posts a game that any account, or any account of `allow_list`, can join
+        // until `expires_at`. The transferred value is escrowed as the creator's stake.
+        #[ink(message, payable)]
+        pub fn open_challenge(
+            &mut self,
+            board_size: u8,
+            options: GameOptions,
+            allow_list: Option<Vec<AccountId>>,
+            expires_at: Timestamp,
+        ) -> Result<GameId, ReversiError> {
+            Board::new(board_size)?;
+            options.check()?;
+            if expires_at <= self.env().block_timestamp() {
+                return Err(ReversiError::ChallengeExpired)
+            }
+
+            let challenge = Challenge {
+                creator: Self::env().caller(),
+                board_size,
+                stake: Self::env().transferred_value(),
+                options,
+                allow_list,
+                expires_at,
+            };
+            let game_id = self.next_game_id();
+            self.challenges.insert(game_id, &challenge);
+            self.env().emit_event(ChallengeOpened {
+                game_id,
+                creator: challenge.creator,
+                board_size,
+                stake: challenge.stake,
+                expires_at,
+            });
+            Ok(game_id)
+        }
+
+        // Elo rating from rated games. Players start at `rating::DEFAULT_RATING`.
+        #[ink(message)]
+        pub fn get_rating(&self, account: AccountId) -> Rating {
+            self.ratings.get(account).unwrap_or(rating::DEFAULT_RATING)
+        }
+
+        // Returns `None` for an account that has not finished any game.
+        #[ink(message)]
+        pub fn get_stats(&self, account: AccountId) -> Option<PlayerStats> {
+            self.stats.get(account)
+        }
+
+        // Page of all players who finished a game, best first. Ties keep the order of the first game.
+        // It reads every player, so it is meant to be queried rather than called in a transaction.
+        #[ink(message)]
+        pub fn get_leaderboard(&self, order: LeaderboardOrder, offset: u32, limit: u32) -> Vec<LeaderboardEntry> {
+            let mut entries = (0..self.ranked_player_count)
+                .filter_map(|index| self.ranked_players.get(index))
+                .map(|account| LeaderboardEntry {
+                    account,
+                    rating: self.get_rating(account),
+                    stats: self.stats.get(account).unwrap_or_default(),
+                })
+                .collect::<Vec<_>>();
+
+            entries.sort_by(|a, b| match order {
+                LeaderboardOrder::Rating => b.rating.cmp(&a.rating).then(b.stats.wins.cmp(&a.stats.wins)),
+                LeaderboardOrder::Wins => b.stats.wins.cmp(&a.stats.wins).then(b.rating.cmp(&a.rating)),
+            });
+            entries
+                .into_iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .collect()
+        }
+
+        // The transferred value is the prize pool, split by `prize_shares` in percent
+        // from first place down. The shares have to add up to 100 unless there is no prize.
+        #[ink(message, payable)]
+        pub fn create_tournament(
+            &mut self,
+            format: Format,
+            board_size: u8,
+            options: GameOptions,
+            prize_shares: Vec<u32>,
+        ) -> Result<TournamentId, ReversiError> {
+            Board::new(board_size)?;
+            options.check()?;
+            match format {
+                Format::Swiss { rounds: 0 } => return Err(ReversiError::NoRounds),
+                Format::Swiss { rounds } if rounds > tournament::MAX_SWISS_ROUNDS => {
+                    return Err(ReversiError::TooManyRounds)
+                }
+                _ => (),
+            }
+            let prize_pool = Self::env().transferred_value();
+            if (prize_pool > 0 || !prize_shares.is_empty())
+                && (prize_shares.iter().any(|&share| share > 100)
+                    || prize_shares.iter().map(|&share| share as u64).sum::<u64>() != 100)
+            {
+                return Err(ReversiError::InvalidPrizeShares)
+            }
+
+            let organiser = Self::env().caller();
+            let tournament_id = self.next_tournament_id;
+            self.next_tournament_id += 1;
+            self.tournaments.insert(
+                tournament_id,
+                &Tournament {
+                    organiser,
+                    format,
+                    board_size,
+                    options,
+                    players: Vec::new(),
+                    table: Table::default(),
+                    bracket: Bracket::default(),
+                    round: 0,
+                    round_count: 0,
+                    games: Vec::new(),
+                    pending_games: 0,
+                    is_finished: false,
+                    prize_pool,
+                    prize_shares,
+                },
+            );
+            self.env().emit_event(TournamentCreated {
+                tournament_id,
+                organiser,
+                format,
+                board_size,
+            });
+            Ok(tournament_id)
+        }
+
+        #[ink(message)]
+        pub fn register(&mut self, tournament_id: TournamentId) -> Result<(), ReversiError> {
+            let mut tournament = self.get_tournament(tournament_id)?;
+            if tournament.round > 0 {
+                return Err(ReversiError::TournamentAlreadyStarted)
+            }
+            let player = Self::env().caller();
+            if tournament.players.contains(&player) {
+                return Err(ReversiError::AlreadyRegistered)
+            }
+            if tournament.players.len() as u32 >= tournament::MAX_PLAYERS {
+                return Err(ReversiError::TournamentFull)
+            }
+
+            tournament.players.push(player);
+            self.tournaments.insert(tournament_id, &tournament);
+            self.env().emit_event(PlayerRegistered {
+                tournament_id,
+                player,
+            });
+            Ok(())
+        }
+
+        // Reorders the registered players, best seed first. Only players who registered
+        // themselves can be seeded, since starting the tournament creates their games.
+        #[ink(message)]
+        pub fn seed_players(&mut self, tournament_id: TournamentId, players: Vec<AccountId>) -> Result<(), ReversiError> {
+            let mut tournament = self.get_tournament(tournament_id)?;
+            if tournament.organiser != Self::env().caller() {
+                return Err(ReversiError::InvalidPlayer)
+            }
+            if tournament.round > 0 {
+                return Err(ReversiError::TournamentAlreadyStarted)
+            }
+            let mut seeds = players.clone();
+            let mut registered = tournament.players.clone();
+            seeds.sort();
+            registered.sort();
+            if seeds != registered {
+                return Err(ReversiError::InvalidSeeds)
+            }
+
+            tournament.players = players;
+            self.tournaments.insert(tournament_id, &tournament);
+            Ok(())
+        }
+
+        // Closes the registration and creates the games of the first round.
+        #[ink(message)]
+        pub fn start_tournament(&mut self, tournament_id: TournamentId) -> Result<(), ReversiError> {
+            let mut tournament = self.get_tournament(tournament_id)?;
+            if tournament.organiser != Self::env().caller() {
+                return Err(ReversiError::InvalidPlayer)
+            }
+            if tournament.round > 0 {
+                return Err(ReversiError::TournamentAlreadyStarted)
+            }
+            let player_count = tournament.players.len() as u32;
+            if player_count < 2 {
+                return Err(ReversiError::NotEnoughPlayers)
+            }
+
+            tournament.table = Table::new(player_count);
+            tournament.bracket = Bracket::new(player_count);
+            tournament.round_count = tournament.format.round_count(player_count);
+            self.start_round(tournament_id, &mut tournament)?;
+            self.tournaments.insert(tournament_id, &tournament);
+            Ok(())
+        }
+
+        // Refunds the prize pool of a tournament that has not started, and deletes it.
+        #[ink(message)]
+        pub fn cancel_tournament(&mut self, tournament_id: TournamentId) -> Result<(), ReversiError> {
+            let tournament = self.get_tournament(tournament_id)?;
+            if tournament.organiser != Self::env().caller() {
+                return Err(ReversiError::InvalidPlayer)
+            }
+            if tournament.round > 0 {
+                return Err(ReversiError::TournamentAlreadyStarted)
+            }
+
+            self.tournaments.remove(tournament_id);
+            self.transfer(tournament.organiser, tournament.prize_pool)?;
+            self.env().emit_event(TournamentCancelled {
+                tournament_id,
+                organiser: tournament.organiser,
+            });
+            Ok(())
+        }
+
+        // Pays out every prize the caller won and has not withdrawn yet.
+        #[ink(message)]
+        pub fn withdraw_prize(&mut self) -> Result<(), ReversiError> {
+            let player = Self::env().caller();
+            let prize = self.get_prize(player);
+            if prize == 0 {
+                return Err(ReversiError::NoPrize)
+            }
+
+            // The credit is cleared before the transfer, and restored if it fails.
+            self.prizes.remove(player);
+            if let Err(error) = self.transfer(player, prize) {
+                self.prizes.insert(player, &prize);
+                return Err(error)
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_prize(&self, account: AccountId) -> Balance {
+            self.prizes.get(account).unwrap_or(0)
+        }
+
+        #[ink(message)]
+        pub fn get_tournament(&self, tournament_id: TournamentId) -> Result<Tournament, ReversiError> {
+            self.tournaments.get(tournament_id).ok_or(ReversiError::TournamentNotFound)
+        }
+
+        // Players from first to last, with the tie-breaks used to order them.
+        #[ink(message)]
+        pub fn get_standings(&self, tournament_id: TournamentId) -> Result<Vec<(AccountId, Standing)>, ReversiError> {
+            let tournament = self.get_tournament(tournament_id)?;
+            let standings = tournament.table.standings();
+            Ok(tournament
+                .places()
+                .into_iter()
+                .flatten()
+                .filter_map(|player| standings.iter().find(|standing| standing.player == player))
+                .map(|standing| (tournament.players[standing.player as usize], *standing))
+                .collect())
+        }
+
+        #[ink(message)]
+        pub fn get_challenge(&self, game_id: GameId) -> Result<Challenge, ReversiError> {
+            self.challenges.get(game_id).ok_or(ReversiError::ChallengeNotFound)
+        }
+
+        // Refunds the creator of a challenge that nobody joined before it expired.
+        #[ink(message)]
+        pub fn cancel_challenge(&mut self, game_id: GameId) -> Result<(), ReversiError> {
+            let challenge = self.get_challenge(game_id)?;
+            if challenge.creator != Self::env().caller() {
+                return Err(ReversiError::InvalidPlayer)
+            }
+            if self.env().block_timestamp() < challenge.expires_at {
+                return Err(ReversiError::ChallengeNotExpired)
+            }
+
+            self.challenges.remove(game_id);
+            self.transfer(challenge.creator, challenge.stake)?;
+            self.env().emit_event(ChallengeCancelled {
+                game_id,
+                creator: challenge.creator,
+            });
+            Ok(())
+        }
+
+        // Takes the empty seat of an open challenge, or accepts an invitation from
+        // `create_game`, matching the creator's stake.
+        #[ink(message, payable)]
+        pub fn join_game(&mut self, game_id: GameId) -> Result<(), ReversiError> {
+            if let Some(challenge) = self.challenges.get(game_id) {
+                return self.join_challenge(game_id, challenge)
+            }
+
+            let mut game = self.get_game(game_id)?;
+            if game.is_joined {
+                return Err(ReversiError::GameAlreadyJoined)
+            }
+
+            let player = Self::env().caller();
+            if game.players[1] != player {
+                return Err(ReversiError::InvalidPlayer)
+            }
+
+            let stake = Self::env().transferred_value();
+            if stake != game.stake {
+                return Err(ReversiError::IncorrectStake)
+            }
+
+            game.is_joined = true;
+            self.randomise(game_id, &mut game)?;
+            game.last_move_at = self.env().block_timestamp();
+            self.games.insert(game_id, &game);
+            self.env().emit_event(GameJoined {
+                game_id,
+                player,
+                stake,
+            });
+            Ok(())
+        }
+
+        // Refunds the creator of a game from `create_game` that the opponent has not joined.
+        #[ink(message)]
+        pub fn cancel_game(&mut self, game_id: GameId) -> Result<(), ReversiError> {
+            let game = self.get_game(game_id)?;
+            if game.is_joined {
+                return Err(ReversiError::GameAlreadyJoined)
+            }
+            // Colours are only drawn when the game is joined, so player_1 is still the creator.
+            let creator = Self::env().caller();
+            if game.players[0] != creator {
+                return Err(ReversiError::InvalidPlayer)
+            }
+
+            self.games.remove(game_id);
+            self.transfer(creator, game.stake)?;
+            self.env().emit_event(GameCancelled { game_id, creator });
+            Ok(())
+        }
+
+        // Returns `None` if the game has no move timeout.
+        #[ink(message)]
+        pub fn get_deadline(&self, game_id: GameId) -> Result<Option<Timestamp>, ReversiError> {
+            Ok(self.get_game(game_id)?.deadline())
+        }
+
+        // Colour draw of games created with `ColourAssignment::CommitReveal`.
+        // Each player commits the Blake2x256 hash of a 32 byte secret.
+        #[ink(message)]
+        pub fn commit_colour(&mut self, game_id: GameId, commitment: Hash) -> Result<(), ReversiError> {
+            let mut game = self.get_started_game(game_id)?;
+            let player = Self::env().caller();
+            let index = game.player_index(player).ok_or(ReversiError::InvalidPlayer)?;
+            let draw = game.colour_draw.as_mut().ok_or(ReversiError::NoColourDraw)?;
+            if draw.commitments[index].is_some() {
+                return Err(ReversiError::AlreadyCommitted)
+            }
+            // A copied commitment would let its owner know the result in advance.
+            if draw.commitments[1 - index] == Some(commitment) {
+                return Err(ReversiError::DuplicateCommitment)
+            }
+
+            draw.commitments[index] = Some(commitment);
+            game.last_move_at = self.env().block_timestamp();
+            self.games.insert(game_id, &game);
+            self.env().emit_event(ColourCommitted { game_id, player });
+            Ok(())
+        }
+
+        // Once both players have revealed, the lowest bit of the XOR of their secrets
+        // decides the colours: player_1 keeps Black if it is 0.
+        #[ink(message)]
+        pub fn reveal_colour(&mut self, game_id: GameId, secret: [u8; 32]) -> Result<(), ReversiError> {
+            let mut game = self.get_started_game(game_id)?;
+            let player = Self::env().caller();
+            let index = game.player_index(player).ok_or(ReversiError::InvalidPlayer)?;
+            let draw = game.colour_draw.as_mut().ok_or(ReversiError::NoColourDraw)?;
+            if draw.commitments.contains(&None) {
+                return Err(ReversiError::CommitmentMissing)
+            }
+            if draw.secrets[index].is_some() {
+                return Err(ReversiError::AlreadyRevealed)
+            }
+            if draw.commitments[index] != Some(ColourDraw::commitment(&secret)) {
+                return Err(ReversiError::InvalidSecret)
+            }
+
+            draw.secrets[index] = Some(secret);
+            let secrets = draw.secrets;
+            game.last_move_at = self.env().block_timestamp();
+            self.env().emit_event(ColourRevealed { game_id, player, secret });
+
+            if let [Some(secret_1), Some(secret_2)] = secrets {
+                if (secret_1[31] ^ secret_2[31]) & 1 == 1 {
+                    game.swap_colours();
+                }
+                game.colour_draw = None;
+                self.env().emit_event(ColoursAssigned {
+                    game_id,
+                    black: game.players[0],
+                    white: game.players[1],
+                });
+            }
+            self.games.insert(game_id, &game);
+            Ok(())
+        }
+
+        // Plays a transcript of moves signed off-chain, starting from the current position.
+        // Either player can submit it, and the rules are checked as for `make_move`.
+        // Signing accounts are derived from ECDSA keys, as the Blake2x256 hash of the compressed public key.
+        #[ink(message)]
+        pub fn settle_channel(&mut self, game_id: GameId, transcript: Vec<SignedMove>) -> Result<(), ReversiError> {
+            let mut game = self.get_started_game(game_id)?;
+            if game.colour_draw.is_some() {
+                return Err(ReversiError::GameNotStarted)
+            }
+            let player = Self::env().caller();
+            game.get_opponent(player).ok_or(ReversiError::InvalidPlayer)?;
+            if self.result_claims.contains(game_id) {
+                return Err(ReversiError::ResultClaimPending)
+            }
+            self.replay_transcript(game_id, &game, &transcript)?;
+
+            self.settle_moves(game_id, &mut game, &transcript)?;
+            self.env().emit_event(ChannelSettled {
+                game_id,
+                player,
+                move_count: transcript.len() as u32,
+            });
+            Ok(())
+        }
+
+        // Called by the waiting player when the opponent stops answering off-chain.
+        // The opponent has `DISPUTE_PERIOD` to move on-chain, or loses on `claim_timeout`.
+        #[ink(message)]
+        pub fn start_dispute(&mut self, game_id: GameId) -> Result<(), ReversiError> {
+            let mut game = self.get_started_game(game_id)?;
+            if game.colour_draw.is_some() {
+                return Err(ReversiError::GameNotStarted)
+            }
+            let player = Self::env().caller();
+            if game.get_opponent(player).is_none() || game.is_active(player) {
+                return Err(ReversiError::InvalidPlayer)
+            }
+            if game.dispute_deadline.is_some() {
+                return Err(ReversiError::DisputeAlreadyStarted)
+            }
+
+            let deadline = self.env().block_timestamp().saturating_add(DISPUTE_PERIOD);
+            game.dispute_deadline = Some(deadline);
+            self.games.insert(game_id, &game);
+            self.env().emit_event(DisputeStarted {
+                game_id,
+                player,
+                deadline,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn claim_timeout(&mut self, game_id: GameId) -> Result<(), ReversiError> {
+            let mut game = self.get_started_game(game_id)?;
+            if self.result_claims.contains(game_id) {
+                return Err(ReversiError::ResultClaimPending)
+            }
+
+            // Only the waiting player can claim. During the colour draw,
+            // that is the player who is a step ahead of the opponent.
+            let player = Self::env().caller();
+            let index = game.player_index(player).ok_or(ReversiError::InvalidPlayer)?;
+            let is_waiting = match &game.colour_draw {
+                Some(draw) => draw.progress(index) > draw.progress(1 - index),
+                None => !game.is_active(player),
+            };
+            if !is_waiting {
+                return Err(ReversiError::InvalidPlayer)
+            }
+
+            let deadline = game.deadline().ok_or(ReversiError::NoMoveTimeout)?;
+            if self.env().block_timestamp() <= deadline {
+                return Err(ReversiError::DeadlineNotReached)
+            }
+
+            let outcome = GameOutcome::TimedOut {
+                winner: player,
+                loser: game.players[1 - index],
+            };
+            self.end_game(game_id, &mut game, outcome)?;
+            self.games.insert(game_id, &game);
+            Ok(())
+        }
+
+        // Either player can resign at any time, including during the opponent's turn.
+        #[ink(message)]
+        pub fn resign(&mut self, game_id: GameId) -> Result<(), ReversiError> {
+            let mut game = self.get_started_game(game_id)?;
+
+            let player = Self::env().caller();
+            let opponent = game.get_opponent(player).ok_or(ReversiError::InvalidPlayer)?;
+
+            self.env().emit_event(PlayerResigned { game_id, player });
+            let outcome = GameOutcome::Resigned {
+                winner: opponent,
+                loser: player,
+            };
+            self.end_game(game_id, &mut game, outcome)?;
+            self.games.insert(game_id, &game);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn offer_draw(&mut self, game_id: GameId) -> Result<(), ReversiError> {
+            let mut game = self.get_started_game(game_id)?;
+            let player = Self::env().caller();
+            if game.get_opponent(player).is_none() {
+                return Err(ReversiError::InvalidPlayer)
+            }
+            if game.draw_offer.is_some() {
+                return Err(ReversiError::DrawAlreadyOffered)
+            }
+
+            game.draw_offer = Some(player);
+            self.games.insert(game_id, &game);
+            self.env().emit_event(DrawOffered { game_id, player });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn accept_draw(&mut self, game_id: GameId) -> Result<(), ReversiError> {
+            let mut game = self.get_started_game(game_id)?;
+            Self::check_draw_offer(&game, Self::env().caller())?;
+
+            game.draw_offer = None;
+            let outcome = GameOutcome::Draw {
+                score: game.count_disks(),
+            };
+            self.end_game(game_id, &mut game, outcome)?;
+            self.games.insert(game_id, &game);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn decline_draw(&mut self, game_id: GameId) -> Result<(), ReversiError> {
+            let mut game = self.get_started_game(game_id)?;
+            let player = Self::env().caller();
+            Self::check_draw_offer(&game, player)?;
+
+            game.draw_offer = None;
+            self.games.insert(game_id, &game);
+            self.env().emit_event(DrawDeclined { game_id, player });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_draw_offer(&self, game_id: GameId) -> Result<Option<AccountId>, ReversiError> {
+            Ok(self.get_game(game_id)?.draw_offer)
+        }
+
+        #[ink(message)]
+        pub fn get_moves(&self, game_id: GameId) -> Result<Vec<Move>, ReversiError> {
+            self.get_game(game_id)?;
+            Ok(self.moves.get(game_id).unwrap_or_default())
+        }
+
+        // Rebuilds the board as it was after the first `ply` entries of the move history.
+        #[ink(message)]
+        pub fn get_board_at(&self, game_id: GameId, ply: u32) -> Result<Grid, ReversiError> {
+            let game = self.get_game(game_id)?;
+            let moves = self.moves.get(game_id).unwrap_or_default();
+            if ply as usize > moves.len() {
+                return Err(ReversiError::InvalidPly)
+            }
+
+            let mut replay = Position::new(game.board().size())?;
+            for m in &moves[..ply as usize] {
+                match *m {
+                    Move::Place { x, y } => {
+                        replay.play(x, y)?;
+                    }
+                    Move::Pass => replay.pass()?,
+                }
+            }
+            Ok(replay.board().to_grid())
+        }
+
+        #[ink(message)]
+        pub fn get_legal_moves(&self, game_id: GameId, disk: Disk) -> Result<Vec<(u8, u8)>, ReversiError> {
+            Ok(self.get_game(game_id)?.board().legal_moves(disk))
+        }
+
+        // Squares the active player would flip by placing a disk at x,y.
+        // Empty if the move is not valid.
+        #[ink(message)]
+        pub fn preview_move(&self, game_id: GameId, x: u8, y: u8) -> Result<Vec<(u8, u8)>, ReversiError> {
+            let game = self.get_started_game(game_id)?;
+            Ok(game.board().flippable_disks(game.position.to_move(), x, y))
+        }
+
+        #[ink(message)]
+        pub fn get_stake(&self, game_id: GameId) -> Result<Balance, ReversiError> {
+            Ok(self.get_game(game_id)?.stake)
+        }
+
+        #[ink(message)]
+        pub fn is_game_over(&self, game_id: GameId) -> Result<bool, ReversiError> {
+            Ok(self.get_game(game_id)?.is_game_over())
+        }
+
+        #[ink(message)]
+        pub fn get_players(&self, game_id: GameId) -> Result<[AccountId; 2], ReversiError> {
+            Ok(self.get_game(game_id)?.players)
+        }
+
+        #[ink(message)]
+        pub fn get_active_player(&self, game_id: GameId) -> Result<AccountId, ReversiError> {
+            Ok(self.get_game(game_id)?.get_active_player())
+        }
+
+        // `player` can also be a session key of the active player.
+        #[ink(message)]
+        pub fn is_active(&self, game_id: GameId, player: AccountId) -> Result<bool, ReversiError> {
+            Ok(self.get_game(game_id)?.is_active(self.resolve_player(game_id, player)))
+        }
+
+        #[ink(message)]
+        pub fn get_board(&self, game_id: GameId) -> Result<Grid, ReversiError> {
+            Ok(self.get_game(game_id)?.board().to_grid())
+        }
+
+        // Returns `None` if the game ended in a draw.
+        #[ink(message)]
+        pub fn get_winner(&self, game_id: GameId) -> Result<Option<AccountId>, ReversiError> {
+            let game = self.get_game(game_id)?;
+            if !game.is_game_over() {
+                return Err(ReversiError::GameIsNotOver)
+            }
+            Ok(game.outcome.winner())
+        }
+
+        #[ink(message)]
+        pub fn get_outcome(&self, game_id: GameId) -> Result<GameOutcome, ReversiError> {
+            Ok(self.get_game(game_id)?.outcome)
+        }
+
+        // The caller can be the active player or one of its session keys.
+        #[ink(message)]
+        pub fn make_move(&mut self, game_id: GameId, x: u8, y: u8) -> Result<(), ReversiError> {
+            let player = self.resolve_player(game_id, Self::env().caller());
+            self.make_move_for(game_id, player, x, y)
+        }
+
+        // Posts the final board of a game played off-chain. The transferred value is a bond
+        // of at least the stake. Unless the opponent answers with `prove_fraud` within
+        // `CLAIM_PERIOD`, `finalize_result` ends the game with it and refunds the bond.
+        #[ink(message, payable)]
+        pub fn claim_result(&mut self, game_id: GameId, board: Grid, winner: Option<AccountId>) -> Result<(), ReversiError> {
+            let game = self.get_started_game(game_id)?;
+            if game.colour_draw.is_some() {
+                return Err(ReversiError::GameNotStarted)
+            }
+            let claimant = Self::env().caller();
+            game.get_opponent(claimant).ok_or(ReversiError::InvalidPlayer)?;
+            if self.result_claims.contains(game_id) {
+                return Err(ReversiError::ResultAlreadyClaimed)
+            }
+            let bond = Self::env().transferred_value();
+            if bond == 0 || bond < game.stake {
+                return Err(ReversiError::InsufficientBond)
+            }
+
+            let size = game.board().size() as usize;
+            if board.len() != size || board.iter().any(|row| row.len() != size) {
+                return Err(ReversiError::InvalidClaim)
+            }
+            let board = Board::from_grid(board);
+            let position = Position::with_board(board.clone(), Disk::Black);
+            let (black_count, white_count) = position.score();
+            let expected_winner = match black_count.cmp(&white_count) {
+                core::cmp::Ordering::Greater => Some(game.players[0]),
+                core::cmp::Ordering::Less => Some(game.players[1]),
+                core::cmp::Ordering::Equal => None,
+            };
+            if !position.is_terminal() || winner != expected_winner {
+                return Err(ReversiError::InvalidClaim)
+            }
+
+            let deadline = self.env().block_timestamp().saturating_add(CLAIM_PERIOD);
+            self.result_claims.insert(
+                game_id,
+                &ResultClaim {
+                    claimant,
+                    board,
+                    winner,
+                    bond,
+                    deadline,
+                },
+            );
+            self.env().emit_event(ResultClaimed {
+                game_id,
+                claimant,
+                winner,
+                bond,
+                deadline,
+            });
+            Ok(())
+        }
+
+        // Answers a claim with moves signed off-chain by the players, checked as for
+        // `settle_channel`. Moves that finish the game with another board prove the claim
+        // wrong: they are played, and the bond goes to the caller. Anything else leaves
+        // the claim open.
+        #[ink(message)]
+        pub fn prove_fraud(&mut self, game_id: GameId, transcript: Vec<SignedMove>) -> Result<(), ReversiError> {
+            let claim = self.get_result_claim(game_id)?;
+            let mut game = self.get_started_game(game_id)?;
+            let challenger = Self::env().caller();
+            if game.get_opponent(claim.claimant) != Some(challenger) {
+                return Err(ReversiError::InvalidPlayer)
+            }
+            let position = self.replay_transcript(game_id, &game, &transcript)?;
+            if !position.is_terminal() {
+                return Err(ReversiError::TranscriptNotFinished)
+            }
+            // The winner follows from the board, so the same board is the same result.
+            if *position.board() == claim.board {
+                return Err(ReversiError::ClaimIsValid)
+            }
+
+            self.result_claims.remove(game_id);
+            self.settle_moves(game_id, &mut game, &transcript)?;
+            self.transfer(challenger, claim.bond)?;
+            self.env().emit_event(FraudProven {
+                game_id,
+                claimant: claim.claimant,
+                challenger,
+            });
+            Ok(())
+        }
+
+        // Ends the game with a claim that nobody answered in time, and refunds its bond.
+        #[ink(message)]
+        pub fn finalize_result(&mut self, game_id: GameId) -> Result<(), ReversiError> {
+            let claim = self.get_result_claim(game_id)?;
+            let mut game = self.get_started_game(game_id)?;
+            if self.env().block_timestamp() <= claim.deadline {
+                return Err(ReversiError::DeadlineNotReached)
+            }
+
+            game.position = Position::with_board(claim.board, game.position.to_move());
+            let score = game.count_disks();
+            let outcome = match claim.winner {
+                Some(winner) => GameOutcome::Won {
+                    winner,
+                    loser: game.get_opponent(winner).ok_or(ReversiError::InvalidPlayer)?,
+                    score,
+                },
+                None => GameOutcome::Draw { score },
+            };
+            // Ending the game refunds the bond.
+            self.end_game(game_id, &mut game, outcome)?;
+            self.games.insert(game_id, &game);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_result_claim(&self, game_id: GameId) -> Result<ResultClaim, ReversiError> {
+            self.result_claims.get(game_id).ok_or(ReversiError::NoResultClaim)
+        }
+
+        // Lets `key` call `make_move` for the caller in this game until block `expires_at`.
+        // Registering the same key again replaces its expiry. A key of the opponent,
+        // even an expired one, cannot be taken over.
+        #[ink(message)]
+        pub fn register_session_key(
+            &mut self,
+            game_id: GameId,
+            key: AccountId,
+            expires_at: BlockNumber,
+        ) -> Result<(), ReversiError> {
+            let game = self.get_game(game_id)?;
+            if game.is_game_over() {
+                return Err(ReversiError::GameIsOver)
+            }
+            let player = Self::env().caller();
+            if game.get_opponent(player).is_none() || game.players.contains(&key) {
+                return Err(ReversiError::InvalidPlayer)
+            }
+            if expires_at <= self.env().block_number() {
+                return Err(ReversiError::SessionKeyExpired)
+            }
+            if self
+                .session_keys
+                .get((game_id, key))
+                .is_some_and(|session_key| session_key.player != player)
+            {
+                return Err(ReversiError::SessionKeyInUse)
+            }
+
+            self.session_keys
+                .insert((game_id, key), &SessionKey { player, expires_at });
+            self.env().emit_event(SessionKeyRegistered {
+                game_id,
+                player,
+                key,
+                expires_at,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn revoke_session_key(&mut self, game_id: GameId, key: AccountId) -> Result<(), ReversiError> {
+            let session_key = self.get_session_key(game_id, key)?;
+            let player = Self::env().caller();
+            if session_key.player != player {
+                return Err(ReversiError::InvalidPlayer)
+            }
+
+            self.session_keys.remove((game_id, key));
+            self.env().emit_event(SessionKeyRevoked { game_id, player, key });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_session_key(&self, game_id: GameId, key: AccountId) -> Result<SessionKey, ReversiError> {
+            self.session_keys
+                .get((game_id, key))
+                .ok_or(ReversiError::SessionKeyNotFound)
+        }
+
+        // Plays a move signed by the player, so that anyone can submit it and pay the fees.
+        // The signature is an ECDSA signature of `relayed_move_message`, and the player's
+        // account is the Blake2x256 hash of the compressed public key.
+        #[ink(message)]
+        pub fn make_move_signed(
+            &mut self,
+            game_id: GameId,
+            x: u8,
+            y: u8,
+            nonce: u64,
+            signature: [u8; 65],
+        ) -> Result<(), ReversiError> {
+            let message = self.relayed_move_message(game_id, x, y, nonce);
+            let player = self
+                .recover_signer(&signature, &message)
+                .ok_or(ReversiError::InvalidSignature)?;
+            if nonce != self.get_nonce(player) {
+                return Err(ReversiError::InvalidNonce)
+            }
+
+            self.make_move_for(game_id, player, x, y)?;
+            self.nonces.insert(player, &(nonce + 1));
+            Ok(())
+        }
+
+        // Nonce the next relayed move of `account` has to be signed with.
+        #[ink(message)]
+        pub fn get_nonce(&self, account: AccountId) -> u64 {
+            self.nonces.get(account).unwrap_or(0)
+        }
+
+        fn make_move_for(&mut self, game_id: GameId, player: AccountId, x: u8, y: u8) -> Result<(), ReversiError> {
+            let mut game = self.get_started_game(game_id)?;
+            if game.colour_draw.is_some() {
+                return Err(ReversiError::GameNotStarted)
+            }
+
+            if !game.is_active(player) {
+                return Err(ReversiError::InvalidPlayer)
+            }
+
+            let mut moves = self.moves.get(game_id).unwrap_or_default();
+            self.play_move(game_id, &mut game, &mut moves, player, x, y)?;
+            self.games.insert(game_id, &game);
+            self.moves.insert(game_id, &moves);
+            Ok(())
+        }
+
+        // Position a channel transcript leads to from the game's current position.
+        // Checks the moves and their signatures without playing them.
+        fn replay_transcript(&self, game_id: GameId, game: &Game, transcript: &[SignedMove]) -> Result<Position, ReversiError> {
+            let mut position = game.position.clone();
+            let mut ply = self.moves.get(game_id).unwrap_or_default().len() as u32;
+            for signed_move in transcript {
+                if position.is_terminal() {
+                    return Err(ReversiError::GameIsOver)
+                }
+                let SignedMove { x, y, signature } = *signed_move;
+                let mover = match position.to_move() {
+                    Disk::Black => game.players[0],
+                    Disk::White => game.players[1],
+                };
+
+                position.play(x, y)?;
+                let message = self.channel_message(game_id, ply, x, y, position.board());
+                if self.recover_signer(&signature, &message) != Some(mover) {
+                    return Err(ReversiError::InvalidSignature)
+                }
+                ply += 1;
+                // As in `play_move`, a player without a valid place passes.
+                if !position.is_terminal() && position.legal_moves().is_empty() {
+                    position.pass()?;
+                    ply += 1;
+                }
+            }
+            Ok(position)
+        }
+
+        // Plays a transcript checked by `replay_transcript`, and stores the game and its moves.
+        fn settle_moves(&mut self, game_id: GameId, game: &mut Game, transcript: &[SignedMove]) -> Result<(), ReversiError> {
+            let mut moves = self.moves.get(game_id).unwrap_or_default();
+            for signed_move in transcript {
+                let mover = game.get_active_player();
+                self.play_move(game_id, game, &mut moves, mover, signed_move.x, signed_move.y)?;
+            }
+            self.games.insert(game_id, game);
+            self.moves.insert(game_id, &moves);
+            Ok(())
+        }
+
+        // Lets `advance_bot` play the caller's seat, or stops it. Only the contract holding a
+        // seat can declare it, including before the game is joined. Bots cannot draw colours
+        // by commit-reveal, so the seat of a game in a colour draw cannot change.
+        #[ink(message)]
+        pub fn set_bot_seat(&mut self, game_id: GameId, is_bot: bool) -> Result<(), ReversiError> {
+            let mut game = self.get_game(game_id)?;
+            if game.is_game_over() {
+                return Err(ReversiError::GameIsOver)
+            }
+            let player = Self::env().caller();
+            let index = game.player_index(player).ok_or(ReversiError::InvalidPlayer)?;
+            if !self.env().is_contract(&player) {
+                return Err(ReversiError::NotAContract)
+            }
+            if game.colour_draw.is_some() {
+                return Err(ReversiError::BotNotSupported)
+            }
+
+            game.bots[index] = is_bot;
+            self.games.insert(game_id, &game);
+            self.env().emit_event(BotSeatChanged { game_id, player, is_bot });
+            Ok(())
+        }
+
+        // Seats of player_1 and player_2 played by `advance_bot`.
+        #[ink(message)]
+        pub fn get_bot_seats(&self, game_id: GameId) -> Result<[bool; 2], ReversiError> {
+            Ok(self.get_game(game_id)?.bots)
+        }
+
+        // Asks the bot whose turn it is for a move and plays it. Anyone can call it.
+        #[ink(message)]
+        pub fn advance_bot(&mut self, game_id: GameId) -> Result<(), ReversiError> {
+            let game = self.get_started_game(game_id)?;
+            if game.colour_draw.is_some() {
+                return Err(ReversiError::GameNotStarted)
+            }
+            if self.result_claims.contains(game_id) {
+                return Err(ReversiError::ResultClaimPending)
+            }
+            let bot_account = game.get_active_player();
+            let index = game.player_index(bot_account).ok_or(ReversiError::InvalidPlayer)?;
+            if !game.bots[index] {
+                return Err(ReversiError::NotBotTurn)
+            }
+            if !self.env().is_contract(&bot_account) {
+                return Err(ReversiError::NotAContract)
+            }
+
+            let bot: ink::contract_ref!(ReversiBot, Environment) = bot_account.into();
+            let reply = bot
+                .call()
+                .choose_move(game.board().clone(), game.position.to_move())
+                .try_invoke();
+            self.play_bot_reply(game_id, game, reply)
+        }
+
+        // Plays the place chosen by the bot whose turn it is. A bot that chose an
+        // invalid place, or whose call failed, forfeits the game.
+        fn play_bot_reply(&mut self, game_id: GameId, mut game: Game, reply: BotReply) -> Result<(), ReversiError> {
+            let bot_account = game.get_active_player();
+            match reply {
+                Ok(Ok((x, y))) if game.board().is_valid_place(game.position.to_move(), x, y) => {
+                    let mut moves = self.moves.get(game_id).unwrap_or_default();
+                    self.play_move(game_id, &mut game, &mut moves, bot_account, x, y)?;
+                    self.moves.insert(game_id, &moves);
+                }
+                _ => {
+                    let outcome = GameOutcome::Forfeited {
+                        winner: game.get_opponent(bot_account).ok_or(ReversiError::InvalidPlayer)?,
+                        loser: bot_account,
+                    };
+                    self.end_game(game_id, &mut game, outcome)?;
+                }
+            }
+            self.games.insert(game_id, &game);
+            Ok(())
+        }
+
+        // Plays x,y for `player`, who has to be the active player, then passes for the
+        // opponent if needed or ends the game. The caller stores the game and its moves.
+        fn play_move(
+            &mut self,
+            game_id: GameId,
+            game: &mut Game,
+            moves: &mut Vec<Move>,
+            player: AccountId,
+            x: u8,
+            y: u8,
+        ) -> Result<(), ReversiError> {
+            if self.result_claims.contains(game_id) {
+                return Err(ReversiError::ResultClaimPending)
+            }
+
+            // Moving instead of answering declines the opponent's draw offer.
+            if game.draw_offer.is_some_and(|offerer| offerer != player) {
+                game.draw_offer = None;
+            }
+
+            let flipped = game.position.play(x, y)?;
+            game.last_move_at = self.env().block_timestamp();
+            // Any move answers a dispute.
+            game.dispute_deadline = None;
+            moves.push(Move::Place { x, y });
+            self.env().emit_event(MovePlayed {
+                game_id,
+                player,
+                x,
+                y,
+                flipped,
+            });
+
+            // Neither player can put disk? If yes, game is over, count disks and decide winner.
+            // Opponent cannot put disk? If yes, opponent passes and same player's turn again.
+            if game.position.is_terminal() {
+                let score = game.count_disks();
+                let (player_1_disk_count, player_2_disk_count) = score;
+                let outcome = if player_1_disk_count > player_2_disk_count {
+                    GameOutcome::Won { winner: game.players[0], loser: game.players[1], score }
+                } else if player_1_disk_count < player_2_disk_count {
+                    GameOutcome::Won { winner: game.players[1], loser: game.players[0], score }
+                } else {
+                    GameOutcome::Draw { score }
+                };
+                self.end_game(game_id, game, outcome)?;
+            } else if game.position.legal_moves().is_empty() {
+                self.env().emit_event(TurnPassed {
+                    game_id,
+                    player: game.get_active_player(),
+                });
+                game.position.pass()?;
+                moves.push(Move::Pass);
+            }
+            Ok(())
+        }
+
+        fn end_game(&mut self, game_id: GameId, game: &mut Game, outcome: GameOutcome) -> Result<(), ReversiError> {
+            game.outcome = outcome;
+            self.pay_out(game)?;
+            // A pending result claim has nothing left to settle.
+            if let Some(claim) = self.result_claims.take(game_id) {
+                self.transfer(claim.claimant, claim.bond)?;
+            }
+            if game.options.rated {
+                self.update_ratings(game);
+            }
+            self.update_stats(game);
+
+            let (black_count, white_count) = game.count_disks();
+            self.env().emit_event(GameEnded {
+                game_id,
+                winner: outcome.winner(),
+                black_count,
+                white_count,
+            });
+
+            if let Some(tournament_game) = self.tournament_games.get(game_id) {
+                self.record_tournament_game(tournament_game, game)?;
+            }
+            Ok(())
+        }
+
+        fn record_tournament_game(&mut self, tournament_game: TournamentGame, game: &Game) -> Result<(), ReversiError> {
+            let tournament_id = tournament_game.tournament_id;
+            let mut tournament = self.get_tournament(tournament_id)?;
+            let winner = match game.outcome.winner() {
+                Some(winner) if winner == game.players[0] => Some(Disk::Black),
+                Some(_) => Some(Disk::White),
+                None => None,
+            };
+            // The colours may have been drawn again by commit-reveal.
+            let pairing = match tournament_game.pairing {
+                pairing if tournament.players[pairing.black as usize] == game.players[0] => pairing,
+                pairing => Pairing {
+                    black: pairing.white,
+                    white: pairing.black,
+                },
+            };
+            tournament.table.record_game(pairing, game.count_disks(), winner);
+
+            if let Format::Knockout { tiebreak } = tournament.format {
+                let loser = match (winner, tiebreak) {
+                    (Some(Disk::Black), _) => pairing.white,
+                    (Some(Disk::White), _) => pairing.black,
+                    (None, tournament::Tiebreak::HigherSeed) => pairing.black.max(pairing.white),
+                    (None, tournament::Tiebreak::Rematch) => {
+                        let rematch = Pairing {
+                            black: pairing.white,
+                            white: pairing.black,
+                        };
+                        self.create_tournament_game(tournament_id, &mut tournament, rematch)?;
+                        self.tournaments.insert(tournament_id, &tournament);
+                        return Ok(())
+                    }
+                };
+                tournament.bracket.knock_out(loser);
+            }
+
+            tournament.pending_games -= 1;
+
+            if tournament.pending_games == 0 {
+                self.start_round(tournament_id, &mut tournament)?;
+            }
+            self.tournaments.insert(tournament_id, &tournament);
+            Ok(())
+        }
+
+        // Creates the games of the next round, or finishes the tournament after the last one.
+        fn start_round(&mut self, tournament_id: TournamentId, tournament: &mut Tournament) -> Result<(), ReversiError> {
+            if matches!(tournament.format, Format::Knockout { .. }) && tournament.round > 0 {
+                tournament.bracket.advance();
+            }
+            if tournament.round == tournament.round_count {
+                return self.finish_tournament(tournament_id, tournament)
+            }
+
+            let player_count = tournament.players.len() as u32;
+            let round = match tournament.format {
+                Format::RoundRobin => tournament::round_robin_round(player_count, tournament.round),
+                Format::Swiss { .. } => tournament.table.swiss_round(),
+                Format::Knockout { .. } => tournament::Round {
+                    pairings: tournament.bracket.pairings(),
+                    bye: None,
+                },
+            };
+            tournament.round += 1;
+            if let Some(bye) = round.bye {
+                tournament.table.record_bye(bye);
+            }
+            self.env().emit_event(RoundStarted {
+                tournament_id,
+                round: tournament.round,
+                bye: round.bye.map(|bye| tournament.players[bye as usize]),
+            });
+
+            for pairing in &round.pairings {
+                self.create_tournament_game(tournament_id, tournament, *pairing)?;
+            }
+            tournament.pending_games = round.pairings.len() as u32;
+            Ok(())
+        }
+
+        fn create_tournament_game(
+            &mut self,
+            tournament_id: TournamentId,
+            tournament: &mut Tournament,
+            pairing: Pairing,
+        ) -> Result<(), ReversiError> {
+            let mut game = Game::new(
+                tournament.board_size,
+                tournament.players[pairing.black as usize],
+                tournament.players[pairing.white as usize],
+                0,
+                tournament.options,
+            )?;
+            // Players accepted their games by registering.
+            game.is_joined = true;
+            let game_id = self.insert_game(game)?;
+            self.tournament_games.insert(
+                game_id,
+                &TournamentGame {
+                    tournament_id,
+                    pairing,
+                },
+            );
+            tournament.games.push(game_id);
+            Ok(())
+        }
+
+        fn finish_tournament(&mut self, tournament_id: TournamentId, tournament: &mut Tournament) -> Result<(), ReversiError> {
+            tournament.is_finished = true;
+            let places = tournament.places();
+            // Prizes are credited rather than sent, so that a failed transfer
+            // cannot block the last game of the tournament.
+            for (player, prize) in tournament::split_prize(tournament.prize_pool, &tournament.prize_shares, &places) {
+                if prize > 0 {
+                    let account = tournament.players[player as usize];
+                    self.prizes.insert(account, &self.get_prize(account).saturating_add(prize));
+                }
+            }
+
+            self.env().emit_event(TournamentFinished {
+                tournament_id,
+                winner: tournament.players[places[0][0] as usize],
+            });
+            Ok(())
+        }
+
+        // Winner takes the whole pot, a draw refunds both stakes.
+        fn pay_out(&mut self, game: &Game) -> Result<(), ReversiError> {
+            if game.stake == 0 {
+                return Ok(())
+            }
+
+            match game.outcome.winner() {
+                Some(winner) => self.transfer(winner, game.stake * 2),
+                None => {
+                    self.transfer(game.players[0], game.stake)?;
+                    self.transfer(game.players[1], game.stake)
+                }
+            }
+        }
+
+        fn update_stats(&mut self, game: &Game) {
+            let (black_count, white_count) = game.count_disks();
+            let winner = game.outcome.winner();
+            let results = [(game.players[0], black_count, white_count), (game.players[1], white_count, black_count)];
+
+            for (player, own_count, opponent_count) in results {
+                let mut stats = match self.stats.get(player) {
+                    Some(stats) => stats,
+                    None => {
+                        self.ranked_players.insert(self.ranked_player_count, &player);
+                        self.ranked_player_count += 1;
+                        PlayerStats::default()
+                    }
+                };
+
+                match winner {
+                    Some(winner) if winner == player => stats.wins += 1,
+                    Some(_) => stats.losses += 1,
+                    None => stats.draws += 1,
+                }
+                stats.games_played += 1;
+                stats.disk_differential += i32::from(own_count) - i32::from(opponent_count);
+                stats.best_score = stats.best_score.max(own_count);
+                self.stats.insert(player, &stats);
+            }
+        }
+
+        fn update_ratings(&mut self, game: &Game) {
+            let ([player, opponent], score) = match game.outcome {
+                GameOutcome::InProgress => return,
+                GameOutcome::Draw { .. } => (game.players, rating::DRAW),
+                GameOutcome::Won { winner, loser, .. }
+                | GameOutcome::Resigned { winner, loser }
+                | GameOutcome::TimedOut { winner, loser }
+                | GameOutcome::Forfeited { winner, loser } => ([winner, loser], rating::WIN),
+            };
+
+            let (player_rating, opponent_rating) =
+                rating::update(self.get_rating(player), self.get_rating(opponent), score);
+            self.ratings.insert(player, &player_rating);
+            self.ratings.insert(opponent, &opponent_rating);
+        }
+
+        fn transfer(&mut self, to: AccountId, value: Balance) -> Result<(), ReversiError> {
+            self.env()
+                .transfer(to, value)
+                .map_err(|_| ReversiError::TransferFailed)
+        }
+
+        // Hash signed for a channel move: x,y played at `ply` of the move history,
+        // and the hash of the board it leads to. Like `relayed_move_message`, it
+        // includes the contract's account so that the transcript only settles here.
+        fn channel_message(&self, game_id: GameId, ply: u32, x: u8, y: u8, board: &Board) -> [u8; 32] {
+            let mut board_hash = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_encoded::<Blake2x256, _>(board, &mut board_hash);
+            let mut message = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_encoded::<Blake2x256, _>(
+                &(self.env().account_id(), game_id, ply, x, y, board_hash),
+                &mut message,
+            );
+            message
+        }
+
+        // Hash signed for `make_move_signed`. It includes the contract's account so that
+        // the signature cannot be replayed on another deployment.
+        fn relayed_move_message(&self, game_id: GameId, x: u8, y: u8, nonce: u64) -> [u8; 32] {
+            let mut message = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_encoded::<Blake2x256, _>(&(self.env().account_id(), game_id, x, y, nonce), &mut message);
+            message
+        }
+
+        fn recover_signer(&self, signature: &[u8; 65], message: &[u8; 32]) -> Option<AccountId> {
+            let public_key = self.env().ecdsa_recover(signature, message).ok()?;
+            let mut account = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_bytes::<Blake2x256>(&public_key, &mut account);
+            Some(AccountId::from(account))
+        }
+
+        // Player owning `account` as an unexpired session key, or `account` itself.
+        fn resolve_player(&self, game_id: GameId, account: AccountId) -> AccountId {
+            match self.session_keys.get((game_id, account)) {
+                Some(session_key) if self.env().block_number() < session_key.expires_at => session_key.player,
+                _ => account,
+            }
+        }
+
+        fn get_game(&self, game_id: GameId) -> Result<Game, ReversiError> {
+            self.games.get(game_id).ok_or(ReversiError::GameNotFound)
+        }
+
+        // Game that both players have joined and that is not over yet.
+        fn get_started_game(&self, game_id: GameId) -> Result<Game, ReversiError> {
+            let game = self.get_game(game_id)?;
+            if game.is_game_over() {
+                return Err(ReversiError::GameIsOver);
+            }
+            if !game.is_joined {
+                return Err(ReversiError::GameNotStarted)
+            }
+            Ok(game)
+        }
+
+        // Only the opponent of the offering player can answer a draw offer.
+        fn check_draw_offer(game: &Game, player: AccountId) -> Result<(), ReversiError> {
+            match (game.draw_offer, game.get_opponent(player)) {
+                (Some(offerer), Some(opponent)) if offerer == opponent => Ok(()),
+                _ => Err(ReversiError::NoDrawOffer),
+            }
+        }
+
+        fn join_challenge(&mut self, game_id: GameId, challenge: Challenge) -> Result<(), ReversiError> {
+            if self.env().block_timestamp() >= challenge.expires_at {
+                return Err(ReversiError::ChallengeExpired)
+            }
+
+            let player = Self::env().caller();
+            if let Some(allow_list) = &challenge.allow_list {
+                if !allow_list.contains(&player) {
+                    return Err(ReversiError::NotInvited)
+                }
+            }
+
+            let stake = Self::env().transferred_value();
+            if stake != challenge.stake {
+                return Err(ReversiError::IncorrectStake)
+            }
+
+            let mut game = Game::new(
+                challenge.board_size,
+                challenge.creator,
+                player,
+                challenge.stake,
+                challenge.options,
+            )?;
+            game.is_joined = true;
+            self.challenges.remove(game_id);
+            self.start_game(game_id, game)?;
+            self.env().emit_event(GameJoined {
+                game_id,
+                player,
+                stake,
+            });
+            Ok(())
+        }
+
+        fn next_game_id(&mut self) -> GameId {
+            let game_id = self.next_game_id;
+            self.next_game_id += 1;
+            game_id
+        }
+
+        fn insert_game(&mut self, game: Game) -> Result<GameId, ReversiError> {
+            let game_id = self.next_game_id();
+            self.start_game(game_id, game)?;
+            Ok(game_id)
+        }
+
+        fn start_game(&mut self, game_id: GameId, mut game: Game) -> Result<(), ReversiError> {
+            // Games waiting for the opponent are randomised by `join_game`.
+            if game.is_joined {
+                self.randomise(game_id, &mut game)?;
+            }
+
+            game.last_move_at = self.env().block_timestamp();
+            self.games.insert(game_id, &game);
+            self.env().emit_event(GameCreated {
+                game_id,
+                player_1: game.players[0],
+                player_2: game.players[1],
+                board_size: game.board().size(),
+            });
+            Ok(())
+        }
+
+        // Draws the colours and plays the random opening from a single call to the random source.
+        // The last byte picks the colours, and each of the first ones picks an opening move.
+        fn randomise(&mut self, game_id: GameId, game: &mut Game) -> Result<(), ReversiError> {
+            if game.options.colours != ColourAssignment::Random && game.options.random_opening == 0 {
+                return Ok(())
+            }
+            let mut subject = [0; 32];
+            subject[..4].copy_from_slice(&game_id.to_le_bytes());
+            let random = self.env().extension().fetch_random(subject)?;
+
+            if game.options.colours == ColourAssignment::Random && random[31] & 1 == 1 {
+                game.swap_colours();
+            }
+
+            let mut moves = Vec::new();
+            for &byte in &random[..game.options.random_opening as usize] {
+                if game.position.is_terminal() {
+                    break
+                }
+                let legal_moves = game.position.legal_moves();
+                if legal_moves.is_empty() {
+                    game.position.pass()?;
+                    moves.push(Move::Pass);
+                } else {
+                    let (x, y) = legal_moves[byte as usize % legal_moves.len()];
+                    game.position.play(x, y)?;
+                    moves.push(Move::Place { x, y });
+                }
+            }
+            if !moves.is_empty() {
+                self.moves.insert(game_id, &moves);
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use ink::env::test::{
+            default_accounts,
+            get_account_balance,
+            recorded_events,
+            set_account_balance,
+            set_block_timestamp,
+            set_callee,
+            set_caller,
+            set_value_transferred,
+        };
+
+        use super::*;
+
+        type Event = <Reversi as ink::reflect::ContractEventBase>::Type;
 
-            Self {
-                players: [player_1, player_2],
-                // No random generator available so far.
-                board_size,
-                active_player_index: 0,
-                board: Board::new(board_size),
-                is_game_over: false,
-                winner: ZERO_ADDRESS.into(),
-            }
+        fn set_disks(reversi: &mut Reversi, game_id: GameId, disks: Grid) {
+            let mut game = reversi.get_game(game_id).unwrap();
+            game.position = Position::with_board(Board::from_grid(disks), game.position.to_move());
+            reversi.games.insert(game_id, &game);
         }
 
-        #[ink(message)]
-        pub fn is_game_over(&self) -> bool {
-            self.is_game_over
-        }
+        #[ink::test]
+        fn constructor_works() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut board_size : usize = 6;
+            let reversi = Reversi::new(board_size as u8, default_accounts.alice, default_accounts.bob).unwrap();
+            assert_eq!(reversi.get_players(0).unwrap(), [default_accounts.alice, default_accounts.bob]);
 
-        #[ink(message)]
-        pub fn get_players(&self) -> [AccountId; 2] {
-            self.players
-        }
+            assert_eq!(reversi.get_active_player(0).unwrap(), default_accounts.alice);
 
-        #[ink(message)]
-        pub fn get_active_player(&self) -> AccountId {
-            self.players[self.active_player_index as usize]
-        }
+            assert!(reversi.is_active(0, default_accounts.alice).unwrap());
+            assert!(!reversi.is_active(0, default_accounts.bob).unwrap());
 
-        #[ink(message)]
-        pub fn is_active(&self, player: AccountId) -> bool {
-            self.players[self.active_player_index as usize] == player
-        }
+            let board = vec![
+                vec![None; board_size],
+                vec![None; board_size],
+                vec![None, None, Some(Disk::White), Some(Disk::Black), None, None],
+                vec![None, None, Some(Disk::Black), Some(Disk::White), None, None],
+                vec![None; board_size],
+                vec![None; board_size],
+            ];
+            assert_eq!(reversi.get_board(0).unwrap(), board);
 
-        #[ink(message)]
-        pub fn get_board(&self) -> Board {
-            self.board.clone()
-        }
+            board_size = 8;
+            let reversi = Reversi::new(board_size as u8, default_accounts.alice, default_accounts.bob).unwrap();
+            let board = vec![
+                vec![None; board_size],
+                vec![None; board_size],
+                vec![None; board_size],
+                vec![None, None, None, Some(Disk::White), Some(Disk::Black), None, None, None],
+                vec![None, None, None, Some(Disk::Black), Some(Disk::White), None, None, None],
+                vec![None; board_size],
+                vec![None; board_size],
+                vec![None; board_size],
+            ];
+            assert_eq!(reversi.get_board(0).unwrap(), board);
 
-        #[ink(message)]
-        pub fn get_winner(&self) -> Result<AccountId, ReversiError> {
-            if !self.is_game_over() {
-                return Err(ReversiError::GameIsNotOver)
-            }
-            Ok(self.winner)
+            board_size = 10;
+            let reversi = Reversi::new(board_size as u8, default_accounts.alice, default_accounts.bob).unwrap();
+            let board = vec![
+                vec![None; board_size],
+                vec![None; board_size],
+                vec![None; board_size],
+                vec![None; board_size],
+                vec![None, None, None, None, Some(Disk::White), Some(Disk::Black), None, None, None, None],
+                vec![None, None, None, None, Some(Disk::Black), Some(Disk::White), None, None, None, None],
+                vec![None; board_size],
+                vec![None; board_size],
+                vec![None; board_size],
+                vec![None; board_size],
+            ];
+            assert_eq!(reversi.get_board(0).unwrap(), board);
         }
 
-        #[ink(message)]
-        pub fn make_move(&mut self, x: u8, y: u8) -> Result<(), ReversiError> {
-            if self.is_game_over {
-                return Err(ReversiError::GameIsOver);
-            }
+        #[ink::test]
+        fn place_disk_ok() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+
+            set_disks(&mut reversi, 0, vec![
+                vec![Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black)],
+                vec![Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black)],
+                vec![Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black)],
+                vec![Some(Disk::White), Some(Disk::White), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black)],
+                vec![None, Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White)],
+                vec![Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black)],
+            ]);
+            //    0  1  2  3  4  5
+            // 0  ⚫ ⚫ ⚫ ⚫ ⚫ ⚫
+            // 1  ⚫ ⚫ ⚫ ⚫ ⚫ ⚫
+            // 2  ⚫ ⚫ ⚫ ⚫ ⚫ ️⚫
+            // 3  ⚪ ⚪ ⚫ ⚫ ⚫ ⚫
+            // 4    ⚪ ⚪ ⚪ ⚪ ⚪
+            // 5  ⚫ ⚫ ⚫ ⚫ ⚫ ⚫ 
+            //
 
-            let player = Self::env().caller();
-            if !self.is_active(player) {
-                return Err(ReversiError::InvalidPlayer)
-            }
+            // Alice (player 1, black disk) place disk at position (0, 4) 
+            let result = reversi.make_move(0, 0, 4);
+            assert!(result.is_ok());
+            assert!(reversi.is_game_over(0).unwrap());
+            assert_eq!(reversi.get_winner(0).unwrap(), Some(default_accounts.alice));
+            assert_eq!(
+                reversi.get_outcome(0).unwrap(),
+                GameOutcome::Won { winner: default_accounts.alice, loser: default_accounts.bob, score: (31, 5) }
+            );
+            let (alice_count, bob_count) = reversi.get_game(0).unwrap().count_disks();
+            assert_eq!(alice_count, 31);
+            assert_eq!(bob_count, 5);
+        }
 
-            let disk = self.get_own_disk(player);
-            self.place_disk(disk, x, y)?;
+        #[ink::test]
+        fn make_move_fails_without_changes() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+            let game = reversi.get_game(0).unwrap();
 
-            // Opposite player can put disk? If yes, opponent's turn next.
-            if self.can_place_disk(disk.opposite()) {
-                self.switch_active_player();
-                return Ok(())
-            }
+            assert!(matches!(reversi.make_move(0, 6, 0), Err(ReversiError::OutOfBounds)));
+            assert!(matches!(reversi.make_move(0, 255, 255), Err(ReversiError::OutOfBounds)));
+            assert!(matches!(reversi.make_move(0, 2, 2), Err(ReversiError::SquareOccupied)));
+            assert!(matches!(reversi.make_move(0, 0, 0), Err(ReversiError::NoDisksFlipped)));
 
-            // Same player can put disk? If yes, same player's turn again.
-            if self.can_place_disk(disk) {
-                return Ok(())
-            }
+            assert_eq!(reversi.get_game(0).unwrap().position, game.position);
+            assert_eq!(reversi.get_moves(0).unwrap(), vec![]);
+            assert_eq!(recorded_events().count(), 1);
+        }
 
-            // Game is over, count disks and decide winner
-            self.is_game_over = true;
-            let (player_1_disk_count, player_2_disk_count) = self.count_disks();
-            if player_1_disk_count > player_2_disk_count {
-                self.winner = self.players[0];
-            } else if player_1_disk_count < player_2_disk_count {
-                self.winner = self.players[1];
-            }
+        #[ink::test]
+        fn make_move_emits_events() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+            set_disks(&mut reversi, 0, vec![
+                vec![None, Some(Disk::White), Some(Disk::Black), None, None, None],
+                vec![None; 6],
+                vec![None; 6],
+                vec![None; 6],
+                vec![None; 6],
+                vec![Some(Disk::Black), Some(Disk::White), None, None, None, None],
+            ]);
 
-            Ok(())
-        }
+            //    0  1  2  3  4  5
+            // 0     ⚪ ⚫
+            // 1
+            // 2
+            // 3
+            // 4
+            // 5  ⚫ ⚪
+            //
 
-        // player_1 uses White disk, player_2 uses Black one.
-        pub fn get_own_disk(&self, player: AccountId) -> Disk {
-            if self.players[0] == player {
-                return Disk::Black;
-            }
-            Disk::White
+            // White has no valid place after this move, so Alice plays again.
+            assert!(reversi.make_move(0, 0, 0).is_ok());
+            assert_eq!(reversi.get_active_player(0).unwrap(), default_accounts.alice);
+            assert!(reversi.make_move(0, 2, 5).is_ok());
+            assert!(reversi.is_game_over(0).unwrap());
+            assert_eq!(
+                reversi.get_moves(0).unwrap(),
+                vec![Move::Place { x: 0, y: 0 }, Move::Pass, Move::Place { x: 2, y: 5 }]
+            );
+
+            let events = recorded_events()
+                .map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(events.len(), 5);
+            assert!(matches!(
+                &events[0],
+                Event::GameCreated(GameCreated { game_id: 0, board_size: 6, .. })
+            ));
+            assert!(matches!(
+                &events[1],
+                Event::MovePlayed(MovePlayed { game_id: 0, player, x: 0, y: 0, flipped: 1 })
+                    if *player == default_accounts.alice
+            ));
+            assert!(matches!(
+                &events[2],
+                Event::TurnPassed(TurnPassed { game_id: 0, player }) if *player == default_accounts.bob
+            ));
+            assert!(matches!(
+                &events[3],
+                Event::MovePlayed(MovePlayed { x: 2, y: 5, flipped: 1, .. })
+            ));
+            assert!(matches!(
+                &events[4],
+                Event::GameEnded(GameEnded { game_id: 0, winner: Some(winner), black_count: 6, white_count: 0 })
+                    if *winner == default_accounts.alice
+            ));
         }
 
-        fn is_valid_place(&self, disk: Disk, x: u8, y: u8) -> bool {
-            let x = x as i32;
-            let y = y as i32;
+        #[ink::test]
+        fn equal_disk_counts_end_in_draw() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+            assert_eq!(reversi.get_outcome(0).unwrap(), GameOutcome::InProgress);
+            assert!(matches!(reversi.get_winner(0), Err(ReversiError::GameIsNotOver)));
+
+            set_disks(&mut reversi, 0, vec![
+                vec![None, Some(Disk::White), Some(Disk::Black), None, None, None],
+                vec![None; 6],
+                vec![None; 6],
+                vec![None; 6],
+                vec![None; 6],
+                vec![None, None, None, Some(Disk::White), Some(Disk::White), Some(Disk::White)],
+            ]);
 
-            // outside of the board
-            if !self.is_inside_board(x, y) {
-                return false;
-            }
-            
-            // A disk is already at x,y position
-            if self.board.disks[y as usize][x as usize].is_some() {
-                return false;
-            }
+            //    0  1  2  3  4  5
+            // 0     ⚪ ⚫
+            // 1
+            // 2
+            // 3
+            // 4
+            // 5           ⚪ ⚪ ⚪
+            //
 
-            // Check all 8 directions.
-            if self.is_flippable_direction(disk, x, y, 1, 0) {
-                return true;
-            }
-            if self.is_flippable_direction(disk, x, y, 0, 1) {
-                return true;
-            }
-            if self.is_flippable_direction(disk, x, y, -1, 0) {
-                return true;
-            }
-            if self.is_flippable_direction(disk, x, y, 0, -1) {
-                return true;
-            }
-            if self.is_flippable_direction(disk, x, y, 1, 1) {
-                return true;
-            }
-            if self.is_flippable_direction(disk, x, y, -1, -1) {
-                return true;
-            }
-            if self.is_flippable_direction(disk, x, y, 1, -1) {
-                return true;
-            }
-            if self.is_flippable_direction(disk, x, y, -1, 1) {
-                return true;
-            }
+            // Neither player has a valid place after this move, and both have 3 disks.
+            assert!(reversi.make_move(0, 0, 0).is_ok());
+            assert!(reversi.is_game_over(0).unwrap());
+            assert_eq!(reversi.get_outcome(0).unwrap(), GameOutcome::Draw { score: (3, 3) });
+            assert_eq!(reversi.get_winner(0).unwrap(), None);
+        }
 
-            false
+        #[ink::test]
+        fn wager_is_paid_to_winner() {
+            let default_accounts = default_accounts::<Environment>();
+            let contract = AccountId::from([0xFF; 32]);
+            set_callee::<Environment>(contract);
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+
+            set_value_transferred::<Environment>(100);
+            let game_id = reversi.create_game(6, default_accounts.bob, GameOptions::default()).unwrap();
+            assert_eq!(reversi.get_stake(game_id).unwrap(), 100);
+            assert!(matches!(reversi.make_move(game_id, 2, 1), Err(ReversiError::GameNotStarted)));
+
+            // Only the invited opponent can join, with a matching stake.
+            set_caller::<Environment>(default_accounts.charlie);
+            assert!(matches!(reversi.join_game(game_id), Err(ReversiError::InvalidPlayer)));
+            set_caller::<Environment>(default_accounts.bob);
+            set_value_transferred::<Environment>(50);
+            assert!(matches!(reversi.join_game(game_id), Err(ReversiError::IncorrectStake)));
+            set_value_transferred::<Environment>(100);
+            assert!(reversi.join_game(game_id).is_ok());
+            assert!(matches!(reversi.join_game(game_id), Err(ReversiError::GameAlreadyJoined)));
+
+            set_disks(&mut reversi, game_id, vec![
+                vec![Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black)],
+                vec![Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black)],
+                vec![Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black)],
+                vec![Some(Disk::White), Some(Disk::White), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black)],
+                vec![None, Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White)],
+                vec![Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black)],
+            ]);
+            set_account_balance::<Environment>(contract, 200);
+            set_account_balance::<Environment>(default_accounts.alice, 0);
+
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.make_move(game_id, 0, 4).is_ok());
+            assert_eq!(reversi.get_winner(game_id).unwrap(), Some(default_accounts.alice));
+            assert_eq!(get_account_balance::<Environment>(default_accounts.alice).unwrap(), 200);
+            assert_eq!(get_account_balance::<Environment>(contract).unwrap(), 0);
         }
 
-        fn place_disk(&mut self, disk: Disk, x: u8, y: u8) -> Result<(), ReversiError> {
-            let mut flipped_disk_count = 0;
+        #[ink::test]
+        fn wager_is_refunded_on_draw() {
+            let default_accounts = default_accounts::<Environment>();
+            let contract = AccountId::from([0xFF; 32]);
+            set_callee::<Environment>(contract);
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+
+            set_value_transferred::<Environment>(100);
+            let game_id = reversi.create_game(6, default_accounts.bob, GameOptions::default()).unwrap();
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.join_game(game_id).is_ok());
+
+            set_disks(&mut reversi, game_id, vec![
+                vec![None, Some(Disk::White), Some(Disk::Black), None, None, None],
+                vec![None; 6],
+                vec![None; 6],
+                vec![None; 6],
+                vec![None; 6],
+                vec![None, None, None, Some(Disk::White), Some(Disk::White), Some(Disk::White)],
+            ]);
+            set_account_balance::<Environment>(contract, 200);
+            set_account_balance::<Environment>(default_accounts.alice, 0);
+            set_account_balance::<Environment>(default_accounts.bob, 0);
+
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.make_move(game_id, 0, 0).is_ok());
+            assert_eq!(reversi.get_outcome(game_id).unwrap(), GameOutcome::Draw { score: (3, 3) });
+            assert_eq!(get_account_balance::<Environment>(default_accounts.alice).unwrap(), 100);
+            assert_eq!(get_account_balance::<Environment>(default_accounts.bob).unwrap(), 100);
+            assert_eq!(get_account_balance::<Environment>(contract).unwrap(), 0);
+        }
 
-            if self.board.disks[y as usize][x as usize].is_some() {
-                return Err(ReversiError::CannotPlaceDisk);
-            }
+        #[ink::test]
+        fn unjoined_game_can_be_cancelled() {
+            let default_accounts = default_accounts::<Environment>();
+            let contract = AccountId::from([0xFF; 32]);
+            set_callee::<Environment>(contract);
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+
+            set_value_transferred::<Environment>(100);
+            let game_id = reversi.create_game(6, default_accounts.bob, GameOptions::default()).unwrap();
+            set_account_balance::<Environment>(contract, 100);
+            set_account_balance::<Environment>(default_accounts.alice, 0);
+
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(matches!(reversi.cancel_game(game_id), Err(ReversiError::InvalidPlayer)));
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.cancel_game(game_id).is_ok());
+            assert_eq!(get_account_balance::<Environment>(default_accounts.alice).unwrap(), 100);
+            assert!(matches!(reversi.get_players(game_id), Err(ReversiError::GameNotFound)));
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(matches!(reversi.join_game(game_id), Err(ReversiError::GameNotFound)));
+
+            // Random colours are drawn once the opponent joins.
+            let mut random = [0; 32];
+            random[31] = 1;
+            ink::env::test::register_chain_extension(MockedRandomness(Some(random)));
+            let options = GameOptions {
+                colours: ColourAssignment::Random,
+                ..Default::default()
+            };
+            set_caller::<Environment>(default_accounts.alice);
+            let game_id = reversi.create_game(6, default_accounts.bob, options).unwrap();
+            assert_eq!(reversi.get_players(game_id).unwrap(), [default_accounts.alice, default_accounts.bob]);
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.join_game(game_id).is_ok());
+            assert_eq!(reversi.get_players(game_id).unwrap(), [default_accounts.bob, default_accounts.alice]);
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(matches!(reversi.cancel_game(game_id), Err(ReversiError::GameAlreadyJoined)));
+        }
 
-            // put disk at x,y position
-            self.board.disks[y as usize][x as usize] = Some(disk);
+        #[ink::test]
+        fn open_challenge_works() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+
+            set_block_timestamp::<Environment>(1_000);
+            assert!(matches!(
+                reversi.open_challenge(7, GameOptions::default(), None, 2_000),
+                Err(ReversiError::OddBoardSize)
+            ));
+            assert!(matches!(
+                reversi.open_challenge(8, GameOptions::default(), None, 1_000),
+                Err(ReversiError::ChallengeExpired)
+            ));
+            let too_long = GameOptions {
+                random_opening: MAX_RANDOM_OPENING + 1,
+                ..Default::default()
+            };
+            assert!(matches!(
+                reversi.open_challenge(8, too_long, None, 2_000),
+                Err(ReversiError::OpeningTooLong)
+            ));
+
+            set_value_transferred::<Environment>(100);
+            let options = GameOptions {
+                move_timeout: Some(60_000),
+                ..Default::default()
+            };
+            let game_id = reversi.open_challenge(8, options, None, 2_000).unwrap();
+            assert!(matches!(reversi.get_players(game_id), Err(ReversiError::GameNotFound)));
+            assert!(matches!(reversi.join_game(game_id), Err(ReversiError::SamePlayer)));
+            assert!(matches!(reversi.cancel_challenge(game_id), Err(ReversiError::ChallengeNotExpired)));
+
+            set_caller::<Environment>(default_accounts.charlie);
+            set_value_transferred::<Environment>(10);
+            assert!(matches!(reversi.join_game(game_id), Err(ReversiError::IncorrectStake)));
+            set_value_transferred::<Environment>(100);
+            assert!(reversi.join_game(game_id).is_ok());
+            assert!(matches!(reversi.get_challenge(game_id), Err(ReversiError::ChallengeNotFound)));
+            assert!(matches!(reversi.join_game(game_id), Err(ReversiError::GameAlreadyJoined)));
+
+            assert_eq!(reversi.get_players(game_id).unwrap(), [default_accounts.alice, default_accounts.charlie]);
+            assert_eq!(reversi.get_stake(game_id).unwrap(), 100);
+            assert_eq!(reversi.get_deadline(game_id).unwrap(), Some(61_000));
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.make_move(game_id, 3, 2).is_ok());
+        }
 
-            // flip opponent disks
-            // returns the number of disks being flipped.
-            let mut flip_disks = |disk: Disk, mut x: i32, mut y: i32, dx: i32, dy: i32| -> u8 {
-                let mut flipped_disk_count = 0;
-                if !self.is_flippable_direction(disk, x, y, dx, dy) {
-                    return flipped_disk_count;
-                }
+        #[ink::test]
+        fn private_challenge_expires() {
+            let default_accounts = default_accounts::<Environment>();
+            let contract = AccountId::from([0xFF; 32]);
+            set_callee::<Environment>(contract);
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+
+            set_block_timestamp::<Environment>(1_000);
+            set_value_transferred::<Environment>(100);
+            let allow_list = vec![default_accounts.bob, default_accounts.django];
+            let game_id = reversi.open_challenge(6, GameOptions::default(), Some(allow_list), 2_000).unwrap();
+
+            set_caller::<Environment>(default_accounts.charlie);
+            assert!(matches!(reversi.join_game(game_id), Err(ReversiError::NotInvited)));
+            assert!(matches!(reversi.cancel_challenge(game_id), Err(ReversiError::InvalidPlayer)));
+
+            set_block_timestamp::<Environment>(2_000);
+            set_caller::<Environment>(default_accounts.django);
+            assert!(matches!(reversi.join_game(game_id), Err(ReversiError::ChallengeExpired)));
+
+            set_account_balance::<Environment>(contract, 100);
+            set_account_balance::<Environment>(default_accounts.alice, 0);
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.cancel_challenge(game_id).is_ok());
+            assert_eq!(get_account_balance::<Environment>(default_accounts.alice).unwrap(), 100);
+            assert!(matches!(reversi.cancel_challenge(game_id), Err(ReversiError::ChallengeNotFound)));
+            assert!(matches!(reversi.join_game(game_id), Err(ReversiError::GameNotFound)));
+        }
 
-                x += dx;
-                y += dy;
+        #[ink::test]
+        fn claim_timeout_works() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
 
-                while self.is_inside_board(x, y) {
-                    if let Some(target_disk) = self.board.disks[y as usize][x as usize] {
-                        if target_disk == disk {
-                            break;
-                        }
+            set_block_timestamp::<Environment>(1_000);
+            let options = GameOptions {
+                move_timeout: Some(60_000),
+                ..Default::default()
+            };
+            let game_id = reversi.create_game(6, default_accounts.bob, options).unwrap();
+            // Games without a stake also wait for the opponent.
+            assert!(matches!(reversi.make_move(game_id, 2, 1), Err(ReversiError::GameNotStarted)));
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.join_game(game_id).is_ok());
+            set_caller::<Environment>(default_accounts.alice);
+            assert_eq!(reversi.get_deadline(game_id).unwrap(), Some(61_000));
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(matches!(reversi.claim_timeout(0), Err(ReversiError::NoMoveTimeout)));
+            set_caller::<Environment>(default_accounts.alice);
+
+            set_block_timestamp::<Environment>(30_000);
+            assert!(reversi.make_move(game_id, 2, 1).is_ok());
+            assert_eq!(reversi.get_deadline(game_id).unwrap(), Some(90_000));
+
+            // Bob is the active player and cannot claim his own timeout.
+            set_block_timestamp::<Environment>(90_001);
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(matches!(reversi.claim_timeout(game_id), Err(ReversiError::InvalidPlayer)));
+
+            set_caller::<Environment>(default_accounts.alice);
+            set_block_timestamp::<Environment>(90_000);
+            assert!(matches!(reversi.claim_timeout(game_id), Err(ReversiError::DeadlineNotReached)));
+            set_block_timestamp::<Environment>(90_001);
+            assert!(reversi.claim_timeout(game_id).is_ok());
+            assert_eq!(
+                reversi.get_outcome(game_id).unwrap(),
+                GameOutcome::TimedOut { winner: default_accounts.alice, loser: default_accounts.bob }
+            );
+
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(matches!(reversi.make_move(game_id, 1, 2), Err(ReversiError::GameIsOver)));
+        }
 
-                        self.board.disks[y as usize][x as usize] = Some(disk);
-                        flipped_disk_count += 1;
-                    }
+        #[ink::test]
+        fn resign_works() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+
+            set_caller::<Environment>(default_accounts.charlie);
+            assert!(matches!(reversi.resign(0), Err(ReversiError::InvalidPlayer)));
+
+            // Bob resigns while it is Alice's turn.
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.resign(0).is_ok());
+            assert_eq!(
+                reversi.get_outcome(0).unwrap(),
+                GameOutcome::Resigned { winner: default_accounts.alice, loser: default_accounts.bob }
+            );
+            assert_eq!(reversi.get_winner(0).unwrap(), Some(default_accounts.alice));
+            assert!(matches!(reversi.resign(0), Err(ReversiError::GameIsOver)));
+
+            let events = recorded_events()
+                .map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                &events[1],
+                Event::PlayerResigned(PlayerResigned { game_id: 0, player }) if *player == default_accounts.bob
+            ));
+            assert!(matches!(
+                &events[2],
+                Event::GameEnded(GameEnded { game_id: 0, winner: Some(winner), black_count: 2, white_count: 2 })
+                    if *winner == default_accounts.alice
+            ));
+        }
 
-                    x += dx;
-                    y += dy;
-                }
+        #[ink::test]
+        fn rated_games_update_ratings() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
 
-                flipped_disk_count
-            };
+            // Game 0 is not rated.
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.resign(0).is_ok());
+            assert_eq!(reversi.get_rating(default_accounts.alice), rating::DEFAULT_RATING);
+            assert_eq!(reversi.get_rating(default_accounts.bob), rating::DEFAULT_RATING);
 
-            let x = x as i32;
-            let y = y as i32;
+            let options = GameOptions {
+                rated: true,
+                ..Default::default()
+            };
+            let game_id = reversi.create_game(6, default_accounts.alice, options).unwrap();
+            // Alice has not accepted the game yet, so it cannot cost her rating points.
+            assert!(matches!(reversi.resign(game_id), Err(ReversiError::GameNotStarted)));
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.join_game(game_id).is_ok());
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.resign(game_id).is_ok());
+            assert_eq!(reversi.get_rating(default_accounts.alice), 1516);
+            assert_eq!(reversi.get_rating(default_accounts.bob), 1484);
+
+            let game_id = reversi.create_game(6, default_accounts.alice, options).unwrap();
+            assert!(matches!(reversi.offer_draw(game_id), Err(ReversiError::GameNotStarted)));
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.join_game(game_id).is_ok());
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.offer_draw(game_id).is_ok());
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.accept_draw(game_id).is_ok());
+            assert_eq!(reversi.get_rating(default_accounts.alice), 1515);
+            assert_eq!(reversi.get_rating(default_accounts.bob), 1485);
+            assert_eq!(reversi.get_rating(default_accounts.charlie), rating::DEFAULT_RATING);
+        }
 
-            // flip disks in all 8 directions.
-            flipped_disk_count += flip_disks(disk, x, y, 1, 0);
-            flipped_disk_count += flip_disks(disk, x, y, 0, 1);
-            flipped_disk_count += flip_disks(disk, x, y, -1, 0);
-            flipped_disk_count += flip_disks(disk, x, y, 0, -1);
-            flipped_disk_count += flip_disks(disk, x, y, 1, 1);
-            flipped_disk_count += flip_disks(disk, x, y, -1, -1);
-            flipped_disk_count += flip_disks(disk, x, y, 1, -1);
-            flipped_disk_count += flip_disks(disk, x, y, -1, 1);
+        #[ink::test]
+        fn stats_and_leaderboard_work() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+            let rated = GameOptions {
+                rated: true,
+                ..Default::default()
+            };
 
-            if flipped_disk_count == 0 {
-                return Err(ReversiError::CannotPlaceDisk)
+            // Alice wins 4 to 1 on the board.
+            let mut disks = vec![vec![None; 6]; 6];
+            disks[0][0] = Some(Disk::Black);
+            disks[0][1] = Some(Disk::Black);
+            disks[1][0] = Some(Disk::Black);
+            disks[1][1] = Some(Disk::White);
+            set_disks(&mut reversi, 0, disks);
+            assert!(reversi.make_move(0, 2, 2).is_ok());
+            assert!(reversi.is_game_over(0).unwrap());
+
+            // Charlie beats Bob twice by resignation, in rated games.
+            set_caller::<Environment>(default_accounts.charlie);
+            for _ in 0..2 {
+                let game_id = reversi.create_game(6, default_accounts.bob, rated).unwrap();
+                set_caller::<Environment>(default_accounts.bob);
+                assert!(reversi.join_game(game_id).is_ok());
+                assert!(reversi.resign(game_id).is_ok());
+                set_caller::<Environment>(default_accounts.charlie);
             }
 
-            Ok(())
+            assert_eq!(reversi.get_stats(default_accounts.django), None);
+            assert_eq!(
+                reversi.get_stats(default_accounts.alice).unwrap(),
+                PlayerStats { wins: 1, losses: 0, draws: 0, games_played: 1, disk_differential: 5, best_score: 5 }
+            );
+            assert_eq!(
+                reversi.get_stats(default_accounts.bob).unwrap(),
+                PlayerStats { wins: 0, losses: 3, draws: 0, games_played: 3, disk_differential: -5, best_score: 2 }
+            );
+            assert_eq!(
+                reversi.get_stats(default_accounts.charlie).unwrap(),
+                PlayerStats { wins: 2, losses: 0, draws: 0, games_played: 2, disk_differential: 0, best_score: 2 }
+            );
+
+            let accounts = |entries: Vec<LeaderboardEntry>| entries.into_iter().map(|entry| entry.account).collect::<Vec<_>>();
+            assert_eq!(
+                accounts(reversi.get_leaderboard(LeaderboardOrder::Rating, 0, 10)),
+                vec![default_accounts.charlie, default_accounts.alice, default_accounts.bob]
+            );
+            assert_eq!(
+                accounts(reversi.get_leaderboard(LeaderboardOrder::Wins, 0, 2)),
+                vec![default_accounts.charlie, default_accounts.alice]
+            );
+            assert_eq!(accounts(reversi.get_leaderboard(LeaderboardOrder::Wins, 2, 2)), vec![default_accounts.bob]);
+            assert!(reversi.get_leaderboard(LeaderboardOrder::Wins, 3, 2).is_empty());
         }
 
-        fn can_place_disk(&self, disk: Disk) -> bool {
-            for i in 0..self.board_size {
-                for j in 0..self.board_size {
-                    if self.is_valid_place(disk, i, j) {
-                        return true;
-                    }
-                }
+        #[ink::test]
+        fn swiss_tournament_works() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+            let players = [default_accounts.alice, default_accounts.bob, default_accounts.charlie, default_accounts.django];
+
+            assert!(matches!(
+                reversi.create_tournament(Format::Swiss { rounds: 0 }, 6, GameOptions::default(), Vec::new()),
+                Err(ReversiError::NoRounds)
+            ));
+            assert!(matches!(
+                reversi.create_tournament(Format::Swiss { rounds: 64 }, 6, GameOptions::default(), Vec::new()),
+                Err(ReversiError::TooManyRounds)
+            ));
+            set_caller::<Environment>(default_accounts.eve);
+            let tournament_id = reversi.create_tournament(Format::Swiss { rounds: 2 }, 6, GameOptions::default(), Vec::new()).unwrap();
+            assert!(matches!(reversi.register(1), Err(ReversiError::TournamentNotFound)));
+
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.register(tournament_id).is_ok());
+            assert!(matches!(reversi.register(tournament_id), Err(ReversiError::AlreadyRegistered)));
+            set_caller::<Environment>(default_accounts.eve);
+            assert!(matches!(reversi.start_tournament(tournament_id), Err(ReversiError::NotEnoughPlayers)));
+            for player in &players[1..] {
+                set_caller::<Environment>(*player);
+                assert!(reversi.register(tournament_id).is_ok());
             }
-            false
+            assert!(matches!(reversi.start_tournament(tournament_id), Err(ReversiError::InvalidPlayer)));
+            set_caller::<Environment>(default_accounts.eve);
+            assert!(reversi.start_tournament(tournament_id).is_ok());
+            assert!(matches!(reversi.register(tournament_id), Err(ReversiError::TournamentAlreadyStarted)));
+
+            // Round 1: Alice plays Bob and Charlie plays Django. Both White players resign.
+            let tournament = reversi.get_tournament(tournament_id).unwrap();
+            assert_eq!(tournament.round, 1);
+            assert_eq!(tournament.games, vec![1, 2]);
+            assert_eq!(reversi.get_players(1).unwrap(), [default_accounts.alice, default_accounts.bob]);
+            assert_eq!(reversi.get_players(2).unwrap(), [default_accounts.charlie, default_accounts.django]);
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.resign(1).is_ok());
+            assert_eq!(reversi.get_tournament(tournament_id).unwrap().round, 1);
+            set_caller::<Environment>(default_accounts.django);
+            assert!(reversi.resign(2).is_ok());
+
+            // Round 2: the winners meet, and so do the losers. Charlie beats Alice, Bob and Django draw.
+            let tournament = reversi.get_tournament(tournament_id).unwrap();
+            assert_eq!(tournament.round, 2);
+            assert_eq!(tournament.games, vec![1, 2, 3, 4]);
+            assert_eq!(reversi.get_players(3).unwrap(), [default_accounts.alice, default_accounts.charlie]);
+            assert_eq!(reversi.get_players(4).unwrap(), [default_accounts.bob, default_accounts.django]);
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.resign(3).is_ok());
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.offer_draw(4).is_ok());
+            set_caller::<Environment>(default_accounts.django);
+            assert!(reversi.accept_draw(4).is_ok());
+
+            let tournament = reversi.get_tournament(tournament_id).unwrap();
+            assert!(tournament.is_finished);
+            assert_eq!(tournament.games.len(), 4);
+            let standings = reversi.get_standings(tournament_id).unwrap();
+            assert_eq!(
+                standings.iter().map(|(player, standing)| (*player, standing.points, standing.buchholz)).collect::<Vec<_>>(),
+                vec![
+                    (default_accounts.charlie, 4, 3),
+                    (default_accounts.alice, 2, 5),
+                    (default_accounts.django, 1, 5),
+                    (default_accounts.bob, 1, 3),
+                ]
+            );
+
+            let events = recorded_events()
+                .map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                events.last().unwrap(),
+                Event::TournamentFinished(TournamentFinished { tournament_id: 0, winner }) if *winner == default_accounts.charlie
+            ));
         }
 
-        fn is_inside_board(&self, x: i32, y: i32) -> bool {
-            if x < 0
-                || x >= self.board_size as i32
-                || y < 0
-                || y >= self.board_size as i32
-            {
-                return false;
+        #[ink::test]
+        fn knockout_tournament_works() {
+            let default_accounts = default_accounts::<Environment>();
+            let contract = AccountId::from([0xFF; 32]);
+            set_callee::<Environment>(contract);
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+            let format = Format::Knockout { tiebreak: tournament::Tiebreak::Rematch };
+
+            set_caller::<Environment>(default_accounts.eve);
+            set_value_transferred::<Environment>(1_000);
+            assert!(matches!(
+                reversi.create_tournament(format, 6, GameOptions::default(), vec![70, 20]),
+                Err(ReversiError::InvalidPrizeShares)
+            ));
+            assert!(matches!(
+                reversi.create_tournament(format, 6, GameOptions::default(), vec![u32::MAX, 101]),
+                Err(ReversiError::InvalidPrizeShares)
+            ));
+            let tournament_id = reversi.create_tournament(format, 6, GameOptions::default(), vec![70, 30]).unwrap();
+            set_account_balance::<Environment>(contract, 1_000);
+            set_value_transferred::<Environment>(0);
+
+            for player in [default_accounts.alice, default_accounts.bob, default_accounts.charlie] {
+                set_caller::<Environment>(player);
+                assert!(reversi.register(tournament_id).is_ok());
             }
-    
-            true
+            set_caller::<Environment>(default_accounts.eve);
+            for seeds in [
+                vec![default_accounts.charlie, default_accounts.alice],
+                vec![default_accounts.charlie, default_accounts.alice, default_accounts.alice],
+                vec![default_accounts.charlie, default_accounts.alice, default_accounts.django],
+            ] {
+                assert!(matches!(reversi.seed_players(tournament_id, seeds), Err(ReversiError::InvalidSeeds)));
+            }
+            let seeds = vec![default_accounts.charlie, default_accounts.alice, default_accounts.bob];
+            assert!(reversi.seed_players(tournament_id, seeds).is_ok());
+            assert!(reversi.start_tournament(tournament_id).is_ok());
+
+            // Charlie, the top seed, has a bye. Alice and Bob draw and meet again with colours swapped.
+            assert_eq!(reversi.get_players(1).unwrap(), [default_accounts.alice, default_accounts.bob]);
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.offer_draw(1).is_ok());
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.accept_draw(1).is_ok());
+            assert_eq!(reversi.get_tournament(tournament_id).unwrap().round, 1);
+            assert_eq!(reversi.get_players(2).unwrap(), [default_accounts.bob, default_accounts.alice]);
+            assert!(reversi.resign(2).is_ok());
+
+            // Final.
+            assert_eq!(reversi.get_tournament(tournament_id).unwrap().round, 2);
+            assert_eq!(reversi.get_players(3).unwrap(), [default_accounts.charlie, default_accounts.alice]);
+            set_account_balance::<Environment>(default_accounts.charlie, 0);
+            set_account_balance::<Environment>(default_accounts.alice, 0);
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.resign(3).is_ok());
+
+            let tournament = reversi.get_tournament(tournament_id).unwrap();
+            assert!(tournament.is_finished);
+            assert_eq!(tournament.games, vec![1, 2, 3]);
+            assert_eq!(
+                reversi.get_standings(tournament_id).unwrap().iter().map(|(player, _)| *player).collect::<Vec<_>>(),
+                vec![default_accounts.charlie, default_accounts.alice, default_accounts.bob]
+            );
+            assert_eq!(reversi.get_prize(default_accounts.charlie), 700);
+            assert_eq!(reversi.get_prize(default_accounts.alice), 300);
+            assert_eq!(get_account_balance::<Environment>(default_accounts.charlie).unwrap(), 0);
+
+            set_caller::<Environment>(default_accounts.charlie);
+            assert!(reversi.withdraw_prize().is_ok());
+            assert!(matches!(reversi.withdraw_prize(), Err(ReversiError::NoPrize)));
+            assert_eq!(get_account_balance::<Environment>(default_accounts.charlie).unwrap(), 700);
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(matches!(reversi.withdraw_prize(), Err(ReversiError::NoPrize)));
         }
 
-        fn switch_active_player(&mut self) {
-            if self.active_player_index == 0 {
-                self.active_player_index = 1;
-                return;
+        #[ink::test]
+        fn tournament_can_be_cancelled() {
+            let default_accounts = default_accounts::<Environment>();
+            let contract = AccountId::from([0xFF; 32]);
+            set_callee::<Environment>(contract);
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+            let format = Format::Swiss { rounds: 1 };
+
+            set_caller::<Environment>(default_accounts.eve);
+            set_value_transferred::<Environment>(1_000);
+            let tournament_id = reversi.create_tournament(format, 6, GameOptions::default(), vec![100]).unwrap();
+            set_account_balance::<Environment>(contract, 1_000);
+            set_account_balance::<Environment>(default_accounts.eve, 0);
+            set_value_transferred::<Environment>(0);
+
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.register(tournament_id).is_ok());
+            assert!(matches!(reversi.cancel_tournament(tournament_id), Err(ReversiError::InvalidPlayer)));
+            set_caller::<Environment>(default_accounts.eve);
+            assert!(reversi.cancel_tournament(tournament_id).is_ok());
+            assert_eq!(get_account_balance::<Environment>(default_accounts.eve).unwrap(), 1_000);
+            assert!(matches!(reversi.get_tournament(tournament_id), Err(ReversiError::TournamentNotFound)));
+
+            // A started tournament plays on.
+            let tournament_id = reversi.create_tournament(format, 6, GameOptions::default(), Vec::new()).unwrap();
+            for player in [default_accounts.alice, default_accounts.bob] {
+                set_caller::<Environment>(player);
+                assert!(reversi.register(tournament_id).is_ok());
             }
-            self.active_player_index = 0;
+            set_caller::<Environment>(default_accounts.eve);
+            assert!(reversi.start_tournament(tournament_id).is_ok());
+            assert!(matches!(reversi.cancel_tournament(tournament_id), Err(ReversiError::TournamentAlreadyStarted)));
         }
-        
-        fn count_disks(&self) -> (u8, u8) {
-            let (mut black_counts, mut white_counts) = (0, 0);
-            for i in 0..self.board_size as usize {
-                for j in 0..self.board_size as usize {
-                    if let Some(disk) = self.board.disks[j][i] {
-                        match disk {
-                            Disk::Black => black_counts += 1,
-                            Disk::White => white_counts += 1,
-                        }
-                    }
-                }
-            }
-            (black_counts, white_counts)
+
+        #[ink::test]
+        fn colour_draw_works() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+            let options = GameOptions {
+                move_timeout: Some(60_000),
+                colours: ColourAssignment::CommitReveal,
+                ..Default::default()
+            };
+            let secret_alice = [1; 32];
+            let secret_bob = [2; 32];
+
+            assert!(matches!(reversi.commit_colour(0, Hash::from([0; 32])), Err(ReversiError::NoColourDraw)));
+            let game_id = reversi.create_game(6, default_accounts.bob, options).unwrap();
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.join_game(game_id).is_ok());
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(matches!(reversi.make_move(game_id, 2, 1), Err(ReversiError::GameNotStarted)));
+            assert!(matches!(reversi.reveal_colour(game_id, secret_alice), Err(ReversiError::CommitmentMissing)));
+            assert!(reversi.commit_colour(game_id, ColourDraw::commitment(&secret_alice)).is_ok());
+            assert!(matches!(
+                reversi.commit_colour(game_id, ColourDraw::commitment(&secret_alice)),
+                Err(ReversiError::AlreadyCommitted)
+            ));
+            assert!(matches!(reversi.reveal_colour(game_id, secret_alice), Err(ReversiError::CommitmentMissing)));
+
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(matches!(
+                reversi.commit_colour(game_id, ColourDraw::commitment(&secret_alice)),
+                Err(ReversiError::DuplicateCommitment)
+            ));
+            assert!(reversi.commit_colour(game_id, ColourDraw::commitment(&secret_bob)).is_ok());
+
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(matches!(reversi.reveal_colour(game_id, secret_bob), Err(ReversiError::InvalidSecret)));
+            assert!(reversi.reveal_colour(game_id, secret_alice).is_ok());
+            assert!(matches!(reversi.reveal_colour(game_id, secret_alice), Err(ReversiError::AlreadyRevealed)));
+            assert!(matches!(reversi.claim_timeout(game_id), Err(ReversiError::DeadlineNotReached)));
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(matches!(reversi.claim_timeout(game_id), Err(ReversiError::InvalidPlayer)));
+
+            // 1 XOR 2 has its lowest bit set, so Bob gets Black.
+            assert!(reversi.reveal_colour(game_id, secret_bob).is_ok());
+            assert_eq!(reversi.get_players(game_id).unwrap(), [default_accounts.bob, default_accounts.alice]);
+            let (x, y) = reversi.get_legal_moves(game_id, Disk::Black).unwrap()[0];
+            assert!(reversi.make_move(game_id, x, y).is_ok());
+
+            let events = recorded_events()
+                .map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                &events[events.len() - 2],
+                Event::ColoursAssigned(ColoursAssigned { black, white, .. })
+                    if *black == default_accounts.bob && *white == default_accounts.alice
+            ));
         }
 
-        fn is_flippable_direction(&self, disk: Disk, mut x: i32, mut y: i32, dx: i32, dy: i32) -> bool {
-            // Check one next square.
-            x += dx;
-            y += dy;
+        #[ink::test]
+        fn colour_draw_times_out() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+            let options = GameOptions {
+                move_timeout: Some(60_000),
+                colours: ColourAssignment::CommitReveal,
+                ..Default::default()
+            };
+            let secret = [3; 32];
+            let no_timeout = GameOptions {
+                move_timeout: None,
+                ..options
+            };
+            assert!(matches!(
+                reversi.create_game(6, default_accounts.bob, no_timeout),
+                Err(ReversiError::NoMoveTimeout)
+            ));
+
+            // Bob commits but never reveals.
+            set_block_timestamp::<Environment>(1_000);
+            let game_id = reversi.create_game(6, default_accounts.bob, options).unwrap();
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.join_game(game_id).is_ok());
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.commit_colour(game_id, ColourDraw::commitment(&secret)).is_ok());
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.commit_colour(game_id, Hash::from([4; 32])).is_ok());
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.reveal_colour(game_id, secret).is_ok());
+
+            set_block_timestamp::<Environment>(61_001);
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(matches!(reversi.claim_timeout(game_id), Err(ReversiError::InvalidPlayer)));
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.claim_timeout(game_id).is_ok());
+            assert_eq!(
+                reversi.get_outcome(game_id).unwrap(),
+                GameOutcome::TimedOut { winner: default_accounts.alice, loser: default_accounts.bob }
+            );
+        }
 
-            if !self.is_inside_board(x, y) {
-                return false;
-            }
+        // Random source that always returns the same bytes, or fails without any.
+        struct MockedRandomness(Option<[u8; 32]>);
 
-            match self.board.disks[y as usize][x as usize] {
-                Some(next_disk) => {
-                    // Cannot place a disk if there's a same color disk at the next square.
-                    if next_disk == disk {
-                        return false;
-                    }
-                },
-                None => {
-                    // Cannot place a disk if the next square is blank.
-                    return false;
-                }
+        impl ink::env::test::ChainExtension for MockedRandomness {
+            fn func_id(&self) -> u32 {
+                crate::randomness::FETCH_RANDOM
             }
 
-            x += dx;
-            y += dy;
-
-            while self.is_inside_board(x, y) {
-                match self.board.disks[y as usize][x as usize] {
-                    Some(target_disk) => {
-                        if target_disk == disk {
-                            // Can place a disk if the same color disk is found.
-                            // Because, this two pair of disks can sandwich opposite color disks.
-                            return true;
-                        }
+            fn call(&mut self, _input: &[u8], output: &mut Vec<u8>) -> u32 {
+                match self.0 {
+                    Some(random) => {
+                        scale::Encode::encode_to(&random, output);
+                        0
                     }
-                    None => {
-                        // CAnnot place a disk if blank square is found.
-                        return false
-                    },
+                    None => 1,
                 }
-
-                x += dx;
-                y += dy;
             }
+        }
 
-            // pair disk to flip opponent's disks not found.
-            false
+        #[ink::test]
+        fn random_colours_and_opening_work() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+            let mut random = [0; 32];
+            random[..4].copy_from_slice(&[0, 1, 2, 3]);
+            random[31] = 1;
+            ink::env::test::register_chain_extension(MockedRandomness(Some(random)));
+
+            let too_long = GameOptions {
+                random_opening: MAX_RANDOM_OPENING + 1,
+                ..Default::default()
+            };
+            assert!(matches!(
+                reversi.create_game(6, default_accounts.bob, too_long),
+                Err(ReversiError::OpeningTooLong)
+            ));
+
+            let options = GameOptions {
+                colours: ColourAssignment::Random,
+                random_opening: 4,
+                ..Default::default()
+            };
+            let game_id = reversi.create_game(6, default_accounts.bob, options).unwrap();
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.join_game(game_id).is_ok());
+            set_caller::<Environment>(default_accounts.alice);
+            assert_eq!(reversi.get_players(game_id).unwrap(), [default_accounts.bob, default_accounts.alice]);
+            assert_eq!(reversi.get_active_player(game_id).unwrap(), default_accounts.bob);
+            let moves = reversi.get_moves(game_id).unwrap();
+            assert_eq!(moves.len(), 4);
+            assert_eq!(moves[0], Move::Place { x: 2, y: 1 });
+            assert_eq!(reversi.get_board_at(game_id, 4).unwrap(), reversi.get_board(game_id).unwrap());
+
+            // Only the colours are drawn, and the last bit keeps them.
+            random[31] = 2;
+            ink::env::test::register_chain_extension(MockedRandomness(Some(random)));
+            let options = GameOptions {
+                colours: ColourAssignment::Random,
+                ..Default::default()
+            };
+            let game_id = reversi.create_game(6, default_accounts.bob, options).unwrap();
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.join_game(game_id).is_ok());
+            set_caller::<Environment>(default_accounts.alice);
+            assert_eq!(reversi.get_players(game_id).unwrap(), [default_accounts.alice, default_accounts.bob]);
+            assert!(reversi.get_moves(game_id).unwrap().is_empty());
+
+            ink::env::test::register_chain_extension(MockedRandomness(None));
+            let game_id = reversi.create_game(6, default_accounts.bob, options).unwrap();
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(matches!(reversi.join_game(game_id), Err(ReversiError::RandomnessUnavailable)));
         }
-    }
 
-    #[cfg(test)]
-    mod tests {
-        use ink::env::test::default_accounts;
+        // ECDSA key and the account derived from it.
+        fn channel_key(seed: u8) -> (secp256k1::SecretKey, AccountId) {
+            let secret_key = secp256k1::SecretKey::from_slice(&[seed; 32]).unwrap();
+            let public_key = secp256k1::PublicKey::from_secret_key(&secp256k1::Secp256k1::new(), &secret_key);
+            let mut account = [0; 32];
+            ink::env::hash_bytes::<Blake2x256>(&public_key.serialize(), &mut account);
+            (secret_key, AccountId::from(account))
+        }
 
-        use super::*;
+        fn sign(key: &secp256k1::SecretKey, message: &[u8; 32]) -> [u8; 65] {
+            let (recovery_id, compact) = secp256k1::Secp256k1::new()
+                .sign_ecdsa_recoverable(&secp256k1::Message::from_slice(message).unwrap(), key)
+                .serialize_compact();
+            let mut signature = [0; 65];
+            signature[..64].copy_from_slice(&compact);
+            signature[64] = recovery_id.to_i32() as u8;
+            signature
+        }
 
-        impl PartialEq for Board {
-            fn eq(&self, other: &Board) -> bool {
-                self.disks.iter().zip(other.disks.iter()).all(|(a,b)| a == b) 
-            }
+        // Plays the first legal move on `position` and signs it.
+        fn sign_next_move(
+            reversi: &Reversi,
+            key: &secp256k1::SecretKey,
+            game_id: GameId,
+            ply: u32,
+            position: &mut Position,
+        ) -> SignedMove {
+            let (x, y) = position.legal_moves()[0];
+            position.play(x, y).unwrap();
+            let message = reversi.channel_message(game_id, ply, x, y, position.board());
+            SignedMove { x, y, signature: sign(key, &message) }
         }
 
         #[ink::test]
-        fn constructor_works() {
+        fn settle_channel_works() {
             let default_accounts = default_accounts::<Environment>();
-            let mut board_size : usize = 6;
-            let reversi = Reversi::new(board_size as u8, default_accounts.alice, default_accounts.bob);
-            assert_eq!(reversi.get_players(), [default_accounts.alice, default_accounts.bob]);
-
-            assert_eq!(reversi.get_active_player(), default_accounts.alice);
-
-            assert_eq!(reversi.is_active(default_accounts.alice), true);
-            assert_eq!(reversi.is_active(default_accounts.bob), false);
-
-            let board = Board {
-                disks: vec![
-                    vec![None; board_size],
-                    vec![None; board_size],
-                    vec![None, None, Some(Disk::White), Some(Disk::Black), None, None],
-                    vec![None, None, Some(Disk::Black), Some(Disk::White), None, None],
-                    vec![None; board_size],
-                    vec![None; board_size],
-                ]
-            };
-            assert_eq!(reversi.get_board(), board);
-
-            board_size = 8;
-            let reversi = Reversi::new(board_size as u8, default_accounts.alice, default_accounts.bob);
-            let board = Board {
-                disks: vec![
-                    vec![None; board_size],
-                    vec![None; board_size],
-                    vec![None; board_size],
-                    vec![None, None, None, Some(Disk::White), Some(Disk::Black), None, None, None],
-                    vec![None, None, None, Some(Disk::Black), Some(Disk::White), None, None, None],
-                    vec![None; board_size],
-                    vec![None; board_size],
-                    vec![None; board_size],
-                ]
-            };
-            assert_eq!(reversi.get_board(), board);
-
-            board_size = 10;
-            let reversi = Reversi::new(board_size as u8, default_accounts.alice, default_accounts.bob);
-            let board = Board {
-                disks: vec![
-                    vec![None; board_size],
-                    vec![None; board_size],
-                    vec![None; board_size],
-                    vec![None; board_size],
-                    vec![None, None, None, None, Some(Disk::White), Some(Disk::Black), None, None, None, None],
-                    vec![None, None, None, None, Some(Disk::Black), Some(Disk::White), None, None, None, None],
-                    vec![None; board_size],
-                    vec![None; board_size],
-                    vec![None; board_size],
-                    vec![None; board_size],
-                ]
-            };
-            assert_eq!(reversi.get_board(), board);
+            let (key_1, player_1) = channel_key(1);
+            let (key_2, player_2) = channel_key(2);
+            let mut reversi = Reversi::new(6, player_1, player_2).unwrap();
+
+            let mut position = Position::new(6).unwrap();
+            let transcript = vec![
+                sign_next_move(&reversi, &key_1, 0, 0, &mut position),
+                sign_next_move(&reversi, &key_2, 0, 1, &mut position),
+                sign_next_move(&reversi, &key_1, 0, 2, &mut position),
+            ];
+
+            set_caller::<Environment>(default_accounts.charlie);
+            assert!(matches!(reversi.settle_channel(0, transcript.clone()), Err(ReversiError::InvalidPlayer)));
+            set_caller::<Environment>(player_2);
+            let mut wrong_signer = Position::new(6).unwrap();
+            assert!(matches!(
+                reversi.settle_channel(0, vec![sign_next_move(&reversi, &key_2, 0, 0, &mut wrong_signer)]),
+                Err(ReversiError::InvalidSignature)
+            ));
+            let mut wrong_ply = Position::new(6).unwrap();
+            assert!(matches!(
+                reversi.settle_channel(0, vec![sign_next_move(&reversi, &key_1, 0, 1, &mut wrong_ply)]),
+                Err(ReversiError::InvalidSignature)
+            ));
+            // Signatures only settle on the deployment they were made for.
+            let contract = ink::env::test::callee::<Environment>();
+            set_callee::<Environment>(AccountId::from([0xEE; 32]));
+            let other_deployment = vec![sign_next_move(&reversi, &key_1, 0, 0, &mut Position::new(6).unwrap())];
+            set_callee::<Environment>(contract);
+            assert!(matches!(reversi.settle_channel(0, other_deployment), Err(ReversiError::InvalidSignature)));
+            let mut illegal = transcript.clone();
+            illegal[1].x = 0;
+            assert!(matches!(reversi.settle_channel(0, illegal), Err(ReversiError::NoDisksFlipped)));
+
+            assert!(reversi.settle_channel(0, transcript.clone()).is_ok());
+            assert_eq!(reversi.get_moves(0).unwrap().len(), 3);
+            assert_eq!(reversi.get_board(0).unwrap(), position.board().to_grid());
+            assert_eq!(reversi.get_active_player(0).unwrap(), player_2);
+            // The transcript cannot be played twice.
+            assert!(matches!(reversi.settle_channel(0, transcript), Err(ReversiError::SquareOccupied)));
         }
 
         #[ink::test]
-        fn is_valid_place_ok() {
+        fn relayed_moves_work() {
             let default_accounts = default_accounts::<Environment>();
-            let reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob);
-
-            //    0  1  2  3  4  5
-            // 0
-            // 1
-            // 2 　　　　⚪️ ⚫️
-            // 3        ⚫️ ⚪️
-            // 4
-            // 5
-            //
+            let (key_1, player_1) = channel_key(1);
+            let (key_2, player_2) = channel_key(2);
+            let mut reversi = Reversi::new(6, player_1, player_2).unwrap();
+            let (x, y) = reversi.get_legal_moves(0, Disk::Black).unwrap()[0];
+
+            // Charlie relays the moves.
+            set_caller::<Environment>(default_accounts.charlie);
+            let signature = sign(&key_1, &reversi.relayed_move_message(0, x, y, 1));
+            assert!(matches!(reversi.make_move_signed(0, x, y, 1, signature), Err(ReversiError::InvalidNonce)));
+            let signature = sign(&key_2, &reversi.relayed_move_message(0, x, y, 0));
+            assert!(matches!(reversi.make_move_signed(0, x, y, 0, signature), Err(ReversiError::InvalidPlayer)));
+            // The signature does not cover another square.
+            let signature = sign(&key_1, &reversi.relayed_move_message(0, x, y, 0));
+            assert!(matches!(reversi.make_move_signed(0, 0, 0, 0, signature), Err(ReversiError::InvalidPlayer)));
+
+            assert!(reversi.make_move_signed(0, x, y, 0, signature).is_ok());
+            assert_eq!(reversi.get_nonce(player_1), 1);
+            assert_eq!(reversi.get_nonce(player_2), 0);
+            assert_eq!(reversi.get_moves(0).unwrap(), vec![Move::Place { x, y }]);
+            assert_eq!(reversi.get_active_player(0).unwrap(), player_2);
+            assert!(matches!(reversi.make_move_signed(0, x, y, 0, signature), Err(ReversiError::InvalidNonce)));
+
+            let events = recorded_events()
+                .map(|event| <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap())
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                events.last().unwrap(),
+                Event::MovePlayed(MovePlayed { game_id: 0, player, .. }) if *player == player_1
+            ));
+        }
 
-            // White
-            assert!(reversi.is_valid_place(Disk::White, 3, 1));
-            assert!(reversi.is_valid_place(Disk::White, 4, 2));
-            assert!(reversi.is_valid_place(Disk::White, 1, 3));
-            assert!(reversi.is_valid_place(Disk::White, 2, 4));
-            assert_eq!(reversi.is_valid_place(Disk::White, 2, 1), false);
-            assert_eq!(reversi.is_valid_place(Disk::White, 2, 2), false);
-            assert_eq!(reversi.is_valid_place(Disk::White, 3, 2), false);
-            assert_eq!(reversi.is_valid_place(Disk::White, 0, 0), false);
-            assert_eq!(reversi.is_valid_place(Disk::White, 5, 6), false);
-
-            // Black
-            assert!(reversi.is_valid_place(Disk::Black, 2, 1));
-            assert!(reversi.is_valid_place(Disk::Black, 4, 3));
-            assert!(reversi.is_valid_place(Disk::Black, 3, 4));
-            assert!(reversi.is_valid_place(Disk::Black, 1, 2));
-            assert_eq!(reversi.is_valid_place(Disk::Black, 3, 1), false);
-            assert_eq!(reversi.is_valid_place(Disk::Black, 2, 2), false);
-            assert_eq!(reversi.is_valid_place(Disk::Black, 3, 2), false);
-            assert_eq!(reversi.is_valid_place(Disk::Black, 0, 0), false);
-            assert_eq!(reversi.is_valid_place(Disk::Black, 6, 5), false);
-        }
-
-        #[ink::test]
-        fn is_valid_place_ok_2() {
+        #[ink::test]
+        fn session_keys_work() {
             let default_accounts = default_accounts::<Environment>();
-            let reversi = Reversi {
-                board_size: 6,
-                players: [default_accounts.alice, default_accounts.bob],
-                active_player_index: 0,
-                winner: ZERO_ADDRESS.into(),
-                is_game_over: false,
-                board: Board {
-                    disks: vec![
-                        vec![None; 6],
-                        vec![None, None, None, Some(Disk::White), None, None],
-                        vec![None, None, Some(Disk::Black), Some(Disk::White), Some(Disk::Black), None],
-                        vec![None, None, Some(Disk::Black), Some(Disk::White), None, None],
-                        vec![None; 6],
-                        vec![None; 6],
-                    ],
-                },
-            };
-
-            //    0  1  2  3  4  5
-            // 0
-            // 1          ⚪️
-            // 2 　　　　⚫️ ⚪️ ⚫ ️ 
-            // 3        ⚫️ ⚪️
-            // 4
-            // 5
-            //
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+            let key = default_accounts.django;
+
+            set_caller::<Environment>(default_accounts.charlie);
+            assert!(matches!(reversi.register_session_key(0, key, 10), Err(ReversiError::InvalidPlayer)));
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(matches!(
+                reversi.register_session_key(0, default_accounts.bob, 10),
+                Err(ReversiError::InvalidPlayer)
+            ));
+            assert!(matches!(reversi.register_session_key(0, key, 0), Err(ReversiError::SessionKeyExpired)));
+            assert!(reversi.register_session_key(0, key, 2).is_ok());
+            assert_eq!(
+                reversi.get_session_key(0, key).unwrap(),
+                SessionKey { player: default_accounts.alice, expires_at: 2 }
+            );
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(matches!(reversi.register_session_key(0, key, 10), Err(ReversiError::SessionKeyInUse)));
+            assert_eq!(reversi.get_session_key(0, key).unwrap().player, default_accounts.alice);
+
+            // The key moves for Alice, but cannot resign for her.
+            set_caller::<Environment>(key);
+            assert!(reversi.is_active(0, key).unwrap());
+            let (x, y) = reversi.get_legal_moves(0, Disk::Black).unwrap()[0];
+            assert!(reversi.make_move(0, x, y).is_ok());
+            assert!(matches!(reversi.resign(0), Err(ReversiError::InvalidPlayer)));
+            set_caller::<Environment>(default_accounts.bob);
+            let (x, y) = reversi.get_legal_moves(0, Disk::White).unwrap()[0];
+            assert!(reversi.make_move(0, x, y).is_ok());
+
+            // The key expires at block 2.
+            ink::env::test::advance_block::<Environment>();
+            ink::env::test::advance_block::<Environment>();
+            set_caller::<Environment>(key);
+            assert!(!reversi.is_active(0, key).unwrap());
+            let (x, y) = reversi.get_legal_moves(0, Disk::Black).unwrap()[0];
+            assert!(matches!(reversi.make_move(0, x, y), Err(ReversiError::InvalidPlayer)));
+
+            assert!(matches!(reversi.revoke_session_key(0, key), Err(ReversiError::InvalidPlayer)));
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.revoke_session_key(0, key).is_ok());
+            assert!(matches!(reversi.get_session_key(0, key), Err(ReversiError::SessionKeyNotFound)));
+        }
 
-            // White
-            assert!(reversi.is_valid_place(Disk::White, 1, 2));
-            assert!(reversi.is_valid_place(Disk::White, 1, 3));
-            assert!(reversi.is_valid_place(Disk::White, 1, 4));
-            assert!(reversi.is_valid_place(Disk::White, 5, 2));
-            assert!(reversi.is_valid_place(Disk::White, 5, 3));
-            assert_eq!(reversi.is_valid_place(Disk::White, 2, 1), false);
-            assert_eq!(reversi.is_valid_place(Disk::White, 2, 2), false);
-            assert_eq!(reversi.is_valid_place(Disk::White, 3, 2), false);
-            assert_eq!(reversi.is_valid_place(Disk::White, 0, 0), false);
-            assert_eq!(reversi.is_valid_place(Disk::White, 5, 6), false);
+        // Game 0 where Black ends the game by playing 2,2, and wins 5 to 0.
+        // Returns Black's signed move and the final board.
+        fn last_move_game(reversi: &mut Reversi, key: &secp256k1::SecretKey) -> (SignedMove, Grid) {
+            let mut disks = vec![vec![None; 6]; 6];
+            disks[0][0] = Some(Disk::Black);
+            disks[0][1] = Some(Disk::Black);
+            disks[1][0] = Some(Disk::Black);
+            disks[1][1] = Some(Disk::White);
+            set_disks(reversi, 0, disks.clone());
+            let mut position = Position::with_board(Board::from_grid(disks), Disk::Black);
+            let signed_move = sign_next_move(reversi, key, 0, 0, &mut position);
+            (signed_move, position.board().to_grid())
+        }
 
-            assert!(reversi.is_valid_place(Disk::Black, 2, 0));
-            assert!(reversi.is_valid_place(Disk::Black, 4, 0));
-            assert!(reversi.is_valid_place(Disk::Black, 4, 4));
-            assert_eq!(reversi.is_valid_place(Disk::Black, 2, 1), false);
-            assert_eq!(reversi.is_valid_place(Disk::Black, 3, 0), false);
-            assert_eq!(reversi.is_valid_place(Disk::Black, 3, 4), false);
+        #[ink::test]
+        fn unchallenged_result_is_finalized() {
+            let contract = AccountId::from([0xFF; 32]);
+            set_callee::<Environment>(contract);
+            let (key_1, player_1) = channel_key(1);
+            let (_, player_2) = channel_key(2);
+            let mut reversi = Reversi::new(6, player_1, player_2).unwrap();
+            let (signed_move, board) = last_move_game(&mut reversi, &key_1);
+
+            set_caller::<Environment>(player_1);
+            set_block_timestamp::<Environment>(1_000);
+            assert!(matches!(
+                reversi.claim_result(0, board.clone(), Some(player_1)),
+                Err(ReversiError::InsufficientBond)
+            ));
+            set_value_transferred::<Environment>(100);
+            assert!(matches!(
+                reversi.claim_result(0, board.clone(), Some(player_2)),
+                Err(ReversiError::InvalidClaim)
+            ));
+            assert!(matches!(
+                reversi.claim_result(0, reversi.get_board(0).unwrap(), None),
+                Err(ReversiError::InvalidClaim)
+            ));
+            assert!(matches!(
+                reversi.claim_result(0, vec![vec![None; 6]; 5], None),
+                Err(ReversiError::InvalidClaim)
+            ));
+            assert!(reversi.claim_result(0, board.clone(), Some(player_1)).is_ok());
+            set_account_balance::<Environment>(contract, 100);
+            set_value_transferred::<Environment>(0);
+            assert!(matches!(
+                reversi.claim_result(0, board.clone(), Some(player_1)),
+                Err(ReversiError::ResultAlreadyClaimed)
+            ));
+            assert!(matches!(reversi.make_move(0, 2, 2), Err(ReversiError::ResultClaimPending)));
+
+            // The signed moves lead to the claimed board.
+            set_caller::<Environment>(player_2);
+            assert!(matches!(reversi.prove_fraud(0, vec![signed_move]), Err(ReversiError::ClaimIsValid)));
+            assert!(matches!(reversi.finalize_result(0), Err(ReversiError::DeadlineNotReached)));
+
+            set_block_timestamp::<Environment>(1_001 + CLAIM_PERIOD);
+            set_account_balance::<Environment>(player_1, 0);
+            assert!(reversi.finalize_result(0).is_ok());
+            assert_eq!(
+                reversi.get_outcome(0).unwrap(),
+                GameOutcome::Won { winner: player_1, loser: player_2, score: (5, 0) }
+            );
+            assert_eq!(reversi.get_board(0).unwrap(), board);
+            // The claimed board is not part of the move history.
+            assert_eq!(reversi.get_board_at(0, 0).unwrap(), Position::new(6).unwrap().board().to_grid());
+            assert_eq!(get_account_balance::<Environment>(player_1).unwrap(), 100);
+            assert!(matches!(reversi.get_result_claim(0), Err(ReversiError::NoResultClaim)));
         }
 
         #[ink::test]
-        fn count_disks_ok() {
-            let default_accounts = default_accounts::<Environment>();
-            let reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob);
-
-            let (white_count, black_count) = reversi.count_disks();
-            assert_eq!(white_count, 2);
-            assert_eq!(black_count, 2);
-
-            let reversi = Reversi {
-                board_size: 6,
-                players: [default_accounts.alice, default_accounts.bob],
-                active_player_index: 0,
-                winner: ZERO_ADDRESS.into(),
-                is_game_over: false,
-                board: Board {
-                    disks: vec![
-                        vec![None; 6],
-                        vec![None, None, None, Some(Disk::White), None, None],
-                        vec![None, None, Some(Disk::Black), Some(Disk::White), Some(Disk::Black), None],
-                        vec![None, None, Some(Disk::Black), Some(Disk::White), None, None],
-                        vec![None; 6],
-                        vec![None; 6],
-                    ],
-                },
-            };
+        fn wrong_result_is_slashed() {
+            let contract = AccountId::from([0xFF; 32]);
+            set_callee::<Environment>(contract);
+            let (key_1, player_1) = channel_key(1);
+            let (_, player_2) = channel_key(2);
+            let mut reversi = Reversi::new(6, player_1, player_2).unwrap();
+            let (signed_move, board) = last_move_game(&mut reversi, &key_1);
+
+            // player_2 claims a board with a single White disk, without any signed move.
+            let mut wrong_board = vec![vec![None; 6]; 6];
+            wrong_board[0][0] = Some(Disk::White);
+            set_caller::<Environment>(player_2);
+            set_value_transferred::<Environment>(100);
+            assert!(reversi.claim_result(0, wrong_board, Some(player_2)).is_ok());
+            set_account_balance::<Environment>(contract, 100);
+            set_value_transferred::<Environment>(0);
+
+            // Only the opponent of the claimant can answer.
+            assert!(matches!(reversi.prove_fraud(0, vec![signed_move.clone()]), Err(ReversiError::InvalidPlayer)));
+            set_caller::<Environment>(player_1);
+            set_account_balance::<Environment>(player_1, 0);
+            assert!(reversi.prove_fraud(0, vec![signed_move]).is_ok());
+            assert_eq!(get_account_balance::<Environment>(player_1).unwrap(), 100);
+            assert!(matches!(reversi.get_result_claim(0), Err(ReversiError::NoResultClaim)));
+
+            // The signed moves are played, and end the game.
+            assert_eq!(
+                reversi.get_outcome(0).unwrap(),
+                GameOutcome::Won { winner: player_1, loser: player_2, score: (5, 0) }
+            );
+            assert_eq!(reversi.get_board(0).unwrap(), board);
+        }
 
-            //    0  1  2  3  4  5
-            // 0
-            // 1          ⚪️
-            // 2 　　　　⚫️ ⚪️ ⚫ ️ 
-            // 3        ⚫️ ⚪️
-            // 4
-            // 5
-            //
+        #[ink::test]
+        fn unfinished_moves_do_not_answer_a_claim() {
+            let contract = AccountId::from([0xFF; 32]);
+            set_callee::<Environment>(contract);
+            let (key_1, player_1) = channel_key(1);
+            let (_, player_2) = channel_key(2);
+            let mut reversi = Reversi::new(6, player_1, player_2).unwrap();
+
+            // player_1 claims a full board of Black disks after a single move.
+            let mut position = Position::new(6).unwrap();
+            let signed_move = sign_next_move(&reversi, &key_1, 0, 0, &mut position);
+            set_caller::<Environment>(player_1);
+            set_value_transferred::<Environment>(100);
+            assert!(reversi.claim_result(0, vec![vec![Some(Disk::Black); 6]; 6], Some(player_1)).is_ok());
+            set_account_balance::<Environment>(contract, 100);
+            set_value_transferred::<Environment>(0);
+
+            set_caller::<Environment>(player_2);
+            assert!(matches!(reversi.claim_timeout(0), Err(ReversiError::ResultClaimPending)));
+            assert!(matches!(reversi.prove_fraud(0, Vec::new()), Err(ReversiError::TranscriptNotFinished)));
+            assert!(matches!(
+                reversi.prove_fraud(0, vec![signed_move]),
+                Err(ReversiError::TranscriptNotFinished)
+            ));
+
+            // The claim stays open, and nothing was played or paid.
+            assert_eq!(reversi.get_result_claim(0).unwrap().bond, 100);
+            assert!(reversi.get_moves(0).unwrap().is_empty());
+            assert!(!reversi.is_game_over(0).unwrap());
+            assert_eq!(get_account_balance::<Environment>(contract).unwrap(), 100);
+        }
 
-            let (white_count, black_count) = reversi.count_disks();
-            assert_eq!(white_count, 3);
-            assert_eq!(black_count, 3);
-
-            let reversi = Reversi {
-                board_size: 6,
-                players: [default_accounts.alice, default_accounts.bob],
-                active_player_index: 0,
-                winner: ZERO_ADDRESS.into(),
-                is_game_over: false,
-                board: Board {
-                    disks: vec![
-                        vec![None, None, None, None, Some(Disk::Black), None],
-                        vec![None, None, None, Some(Disk::Black), None, None],
-                        vec![None, Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White)],
-                        vec![None, Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), None, None],
-                        vec![None, Some(Disk::White), None, Some(Disk::Black), None, None],
-                        vec![None; 6],
-                    ],
-                },
+        // Calls to the bots themselves need a chain, so only the checks around them are tested here.
+        #[ink::test]
+        fn bot_seats_are_checked() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+            assert!(matches!(reversi.advance_bot(0), Err(ReversiError::NotBotTurn)));
+            assert!(matches!(reversi.advance_bot(1), Err(ReversiError::GameNotFound)));
+            ink::env::test::set_contract::<Environment>(default_accounts.bob);
+
+            // Only the seat holder declares its seat, and it has to be a contract.
+            set_caller::<Environment>(default_accounts.charlie);
+            assert!(matches!(reversi.set_bot_seat(0, true), Err(ReversiError::InvalidPlayer)));
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(matches!(reversi.set_bot_seat(0, true), Err(ReversiError::NotAContract)));
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.set_bot_seat(0, true).is_ok());
+            assert_eq!(reversi.get_bot_seats(0).unwrap(), [false, true]);
+            // Bob's seat is a bot, but it is Alice's turn.
+            assert!(matches!(reversi.advance_bot(0), Err(ReversiError::NotBotTurn)));
+
+            // Bots cannot draw colours.
+            set_caller::<Environment>(default_accounts.alice);
+            let options = GameOptions {
+                colours: ColourAssignment::CommitReveal,
+                move_timeout: Some(1_000),
+                ..Default::default()
             };
+            let game_id = reversi.create_game(6, default_accounts.bob, options).unwrap();
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(matches!(reversi.set_bot_seat(game_id, true), Err(ReversiError::BotNotSupported)));
+
+            // Bot seats follow the players when the colours are drawn.
+            let mut random = [0; 32];
+            random[31] = 1;
+            ink::env::test::register_chain_extension(MockedRandomness(Some(random)));
+            set_caller::<Environment>(default_accounts.alice);
+            let options = GameOptions { colours: ColourAssignment::Random, ..Default::default() };
+            let game_id = reversi.create_game(6, default_accounts.bob, options).unwrap();
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.set_bot_seat(game_id, true).is_ok());
+            assert!(reversi.join_game(game_id).is_ok());
+            assert_eq!(reversi.get_players(game_id).unwrap(), [default_accounts.bob, default_accounts.alice]);
+            assert_eq!(reversi.get_bot_seats(game_id).unwrap(), [true, false]);
+            // Bob's contract can also move on its own.
+            let (x, y) = reversi.get_legal_moves(game_id, Disk::Black).unwrap()[0];
+            assert!(reversi.make_move(game_id, x, y).is_ok());
+            assert!(matches!(reversi.advance_bot(game_id), Err(ReversiError::NotBotTurn)));
+        }
 
-            //    0  1  2  3  4  5
-            // 0             ⚫
-            // 1          ⚫
-            // 2 　　 ⚪ ⚪ ⚪ ⚪️ ️⚪️️
-            // 3      ⚫ ⚫ ⚫
-            // 4      ⚪️️   ⚫
-            // 5
-            //
+        #[ink::test]
+        fn bot_replies_are_played_or_forfeited() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+            let forfeited = GameOutcome::Forfeited { winner: default_accounts.bob, loser: default_accounts.alice };
+
+            // A valid place is played.
+            let (x, y) = reversi.get_legal_moves(0, Disk::Black).unwrap()[0];
+            let game = reversi.get_game(0).unwrap();
+            assert!(reversi.play_bot_reply(0, game, Ok(Ok((x, y)))).is_ok());
+            assert_eq!(reversi.get_moves(0).unwrap().len(), 1);
+            assert_eq!(reversi.get_outcome(0).unwrap(), GameOutcome::InProgress);
+
+            // An invalid place, an undecodable reply or a failed call forfeits.
+            let replies: [BotReply; 3] = [
+                Ok(Ok((0, 0))),
+                Ok(Err(ink::LangError::CouldNotReadInput)),
+                Err(ink::env::Error::CalleeTrapped),
+            ];
+            for reply in replies {
+                let game_id = reversi.create_game(6, default_accounts.bob, GameOptions::default()).unwrap();
+                set_caller::<Environment>(default_accounts.bob);
+                assert!(reversi.join_game(game_id).is_ok());
+                set_caller::<Environment>(default_accounts.alice);
+                let game = reversi.get_game(game_id).unwrap();
+                assert!(reversi.play_bot_reply(game_id, game, reply).is_ok());
+                assert_eq!(reversi.get_outcome(game_id).unwrap(), forfeited);
+                assert!(reversi.get_moves(game_id).unwrap().is_empty());
+            }
+        }
 
-            let (white_count, black_count) = reversi.count_disks();
-            assert_eq!(white_count, 6);
-            assert_eq!(black_count, 6);
+        #[ink::test]
+        fn dispute_forces_move() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+
+            // Bob waits for Alice, who answers with a move on-chain.
+            set_block_timestamp::<Environment>(1_000);
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(matches!(reversi.start_dispute(0), Err(ReversiError::InvalidPlayer)));
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.start_dispute(0).is_ok());
+            assert!(matches!(reversi.start_dispute(0), Err(ReversiError::DisputeAlreadyStarted)));
+            assert_eq!(reversi.get_deadline(0).unwrap(), Some(1_000 + DISPUTE_PERIOD));
+            assert!(matches!(reversi.claim_timeout(0), Err(ReversiError::DeadlineNotReached)));
+            set_caller::<Environment>(default_accounts.alice);
+            let (x, y) = reversi.get_legal_moves(0, Disk::Black).unwrap()[0];
+            assert!(reversi.make_move(0, x, y).is_ok());
+            assert_eq!(reversi.get_deadline(0).unwrap(), None);
+
+            // Alice waits for Bob, who does not answer.
+            assert!(reversi.start_dispute(0).is_ok());
+            set_block_timestamp::<Environment>(1_001 + DISPUTE_PERIOD);
+            assert!(reversi.claim_timeout(0).is_ok());
+            assert_eq!(
+                reversi.get_outcome(0).unwrap(),
+                GameOutcome::TimedOut { winner: default_accounts.alice, loser: default_accounts.bob }
+            );
         }
 
         #[ink::test]
-        fn can_place_disk_ok() {
+        fn draw_offer_works() {
             let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+
+            assert!(matches!(reversi.accept_draw(0), Err(ReversiError::NoDrawOffer)));
+            assert!(reversi.offer_draw(0).is_ok());
+            assert_eq!(reversi.get_draw_offer(0).unwrap(), Some(default_accounts.alice));
+            assert!(matches!(reversi.offer_draw(0), Err(ReversiError::DrawAlreadyOffered)));
+
+            // The offering player cannot answer their own offer.
+            assert!(matches!(reversi.accept_draw(0), Err(ReversiError::NoDrawOffer)));
+            assert!(matches!(reversi.decline_draw(0), Err(ReversiError::NoDrawOffer)));
+
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.decline_draw(0).is_ok());
+            assert_eq!(reversi.get_draw_offer(0).unwrap(), None);
+
+            // Offer stands while the offering player moves, and lapses when the opponent moves.
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.offer_draw(0).is_ok());
+            assert!(reversi.make_move(0, 2, 1).is_ok());
+            assert_eq!(reversi.get_draw_offer(0).unwrap(), Some(default_accounts.alice));
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.make_move(0, 1, 1).is_ok());
+            assert_eq!(reversi.get_draw_offer(0).unwrap(), None);
+
+            assert!(reversi.offer_draw(0).is_ok());
+            set_caller::<Environment>(default_accounts.alice);
+            assert!(reversi.accept_draw(0).is_ok());
+            assert_eq!(reversi.get_outcome(0).unwrap(), GameOutcome::Draw { score: (3, 3) });
+            assert_eq!(reversi.get_winner(0).unwrap(), None);
+        }
 
-            let reversi = Reversi {
-                board_size: 6,
-                players: [default_accounts.alice, default_accounts.bob],
-                active_player_index: 0,
-                winner: ZERO_ADDRESS.into(),
-                is_game_over: false,
-                board: Board {
-                    disks: vec![
-                        vec![None, None, None, None, Some(Disk::Black), None],
-                        vec![None, None, None, Some(Disk::Black), None, None],
-                        vec![None, Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White)],
-                        vec![None, Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), None, None],
-                        vec![None, Some(Disk::White), None, Some(Disk::Black), None, None],
-                        vec![None; 6],
-                    ],
-                },
-            };
-            //    0  1  2  3  4  5
-            // 0             ⚫
-            // 1          ⚫
-            // 2 　　 ⚪ ⚪ ⚪ ⚪️ ️⚪️️
-            // 3      ⚫ ⚫ ⚫
-            // 4      ⚪️️   ⚫
-            // 5
-            //
+        #[ink::test]
+        fn move_history_replays() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+            assert_eq!(reversi.get_moves(0).unwrap(), vec![]);
+
+            assert!(reversi.make_move(0, 2, 1).is_ok());
+            let after_first_move = reversi.get_board(0).unwrap();
+            set_caller::<Environment>(default_accounts.bob);
+            assert!(reversi.make_move(0, 1, 1).is_ok());
+
+            assert_eq!(
+                reversi.get_moves(0).unwrap(),
+                vec![Move::Place { x: 2, y: 1 }, Move::Place { x: 1, y: 1 }]
+            );
+            assert_eq!(reversi.get_board_at(0, 0).unwrap(), Board::new(6).unwrap().to_grid());
+            assert_eq!(reversi.get_board_at(0, 1).unwrap(), after_first_move);
+            assert_eq!(reversi.get_board_at(0, 2).unwrap(), reversi.get_board(0).unwrap());
+            assert!(matches!(reversi.get_board_at(0, 3), Err(ReversiError::InvalidPly)));
+            assert!(matches!(reversi.get_moves(1), Err(ReversiError::GameNotFound)));
+        }
 
-            assert!(reversi.can_place_disk(Disk::White));
-            assert!(reversi.can_place_disk(Disk::Black));
-
-            let reversi = Reversi {
-                board_size: 6,
-                players: [default_accounts.alice, default_accounts.bob],
-                active_player_index: 0,
-                winner: ZERO_ADDRESS.into(),
-                is_game_over: false,
-                board: Board {
-                    disks: vec![
-                        vec![None; 6],
-                        vec![None, None, None, Some(Disk::White), None, None],
-                        vec![None, None, Some(Disk::Black), Some(Disk::White), Some(Disk::Black), None],
-                        vec![None, None, Some(Disk::Black), Some(Disk::White), None, None],
-                        vec![None; 6],
-                        vec![None; 6],
-                    ],
-                },
-            };
+        #[ink::test]
+        fn legal_moves_and_preview_work() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+            let grid = vec![
+                vec![None; 6],
+                vec![None, None, None, Some(Disk::White), None, None],
+                vec![None, None, Some(Disk::Black), Some(Disk::White), Some(Disk::Black), None],
+                vec![None, None, Some(Disk::Black), Some(Disk::White), None, None],
+                vec![None; 6],
+                vec![None; 6],
+            ];
+            set_disks(&mut reversi, 0, grid.clone());
 
             //    0  1  2  3  4  5
             // 0
             // 1          ⚪️
-            // 2 　　　　⚫️ ⚪️ ⚫ ️ 
+            // 2 　　　　⚫️ ⚪️ ⚫ ️
             // 3        ⚫️ ⚪️
             // 4
             // 5
             //
 
-            assert!(reversi.can_place_disk(Disk::White));
-            assert!(reversi.can_place_disk(Disk::Black));
-
-            let reversi = Reversi {
-                board_size: 6,
-                players: [default_accounts.alice, default_accounts.bob],
-                active_player_index: 0,
-                winner: ZERO_ADDRESS.into(),
-                is_game_over: false,
-                board: Board {
-                    disks: vec![
-                        vec![Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White)],
-                        vec![Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White)],
-                        vec![Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White)],
-                        vec![Some(Disk::Black), Some(Disk::Black), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White)],
-                        vec![None, Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black)],
-                        vec![Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White)],
-                    ],
-                },
-            };
-            //    0  1  2  3  4  5
-            // 0  ⚪ ⚪ ⚪ ⚪ ⚪️️️️  ⚪️️
-            // 1  ⚪ ⚪ ⚪ ⚪ ⚪️️ ⚪
-            // 2　⚪ ⚪ ⚪ ⚪ ⚪️ ️⚪️️
-            // 3  ⚫ ⚫  ⚪ ⚪ ⚪ ⚪
-            // 4     ⚫  ⚫ ⚫ ⚫ ⚫
-            // 5  ⚪ ⚪  ⚪  ⚪ ⚪ ⚪
-            //
-
-            assert!(reversi.can_place_disk(Disk::White));
-            assert_eq!(reversi.can_place_disk(Disk::Black), false);
+            assert_eq!(
+                reversi.get_legal_moves(0, Disk::Black).unwrap(),
+                vec![(2, 0), (4, 0), (4, 1), (4, 3), (2, 4), (4, 4)]
+            );
+            assert_eq!(
+                reversi.get_legal_moves(0, Disk::White).unwrap(),
+                vec![(1, 1), (5, 1), (1, 2), (5, 2), (1, 3), (5, 3), (1, 4)]
+            );
+
+            // Alice (black) is active.
+            assert_eq!(reversi.preview_move(0, 4, 0).unwrap(), vec![(3, 1)]);
+            assert_eq!(reversi.preview_move(0, 4, 4).unwrap(), vec![(3, 3)]);
+            assert_eq!(reversi.preview_move(0, 4, 1).unwrap(), vec![(3, 2)]);
+            assert_eq!(reversi.preview_move(0, 3, 0).unwrap(), vec![]);
+            assert_eq!(reversi.preview_move(0, 0, 0).unwrap(), vec![]);
+            assert_eq!(reversi.preview_move(0, 6, 0).unwrap(), vec![]);
+            assert_eq!(reversi.get_board(0).unwrap(), grid);
         }
 
         #[ink::test]
-        fn place_disk_ok() {
+        fn invalid_parameters_are_rejected() {
             let default_accounts = default_accounts::<Environment>();
+            let new = |board_size, player_2| Reversi::new(board_size, default_accounts.alice, player_2);
+            assert!(matches!(new(4, default_accounts.bob), Err(ReversiError::BoardTooSmall)));
+            assert!(matches!(new(12, default_accounts.bob), Err(ReversiError::BoardTooBig)));
+            assert!(matches!(new(7, default_accounts.bob), Err(ReversiError::OddBoardSize)));
+            assert!(matches!(new(6, default_accounts.alice), Err(ReversiError::SamePlayer)));
+
+            let mut reversi = new(6, default_accounts.bob).unwrap();
+            assert!(matches!(reversi.create_game(9, default_accounts.bob, GameOptions::default()), Err(ReversiError::OddBoardSize)));
+            assert!(matches!(reversi.create_game(6, default_accounts.alice, GameOptions::default()), Err(ReversiError::SamePlayer)));
+        }
 
-            let mut reversi = Reversi {
-                board_size: 6,
-                players: [default_accounts.alice, default_accounts.bob],
-                active_player_index: 0,
-                winner: ZERO_ADDRESS.into(),
-                is_game_over: false,
-                board: Board {
-                    disks: vec![
-                        vec![Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black)],
-                        vec![Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black)],
-                        vec![Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black)],
-                        vec![Some(Disk::White), Some(Disk::White), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black)],
-                        vec![None, Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White), Some(Disk::White)],
-                        vec![Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black), Some(Disk::Black)],
-                    ],
-                },
-            };
-            //    0  1  2  3  4  5
-            // 0  ⚫ ⚫ ⚫ ⚫ ⚫ ⚫
-            // 1  ⚫ ⚫ ⚫ ⚫ ⚫ ⚫
-            // 2  ⚫ ⚫ ⚫ ⚫ ⚫ ️⚫
-            // 3  ⚪ ⚪ ⚫ ⚫ ⚫ ⚫
-            // 4    ⚪ ⚪ ⚪ ⚪ ⚪
-            // 5  ⚫ ⚫ ⚫ ⚫ ⚫ ⚫ 
-            //
-
-            // Alice (player 1, white disk) place disk at position (0, 4) 
-            let result = reversi.make_move(0, 4);
-            assert!(result.is_ok());
-            assert!(reversi.is_game_over());
-            assert_eq!(reversi.get_winner().unwrap(), default_accounts.alice);
-            let (alice_count, bob_count) = reversi.count_disks();
-            assert_eq!(alice_count, 31);
-            assert_eq!(bob_count, 5);
+        #[ink::test]
+        fn create_game_works() {
+            let default_accounts = default_accounts::<Environment>();
+            let mut reversi = Reversi::new(6, default_accounts.alice, default_accounts.bob).unwrap();
+
+            set_caller::<Environment>(default_accounts.charlie);
+            let game_id = reversi.create_game(8, default_accounts.django, GameOptions::default()).unwrap();
+            assert_eq!(game_id, 1);
+            assert_eq!(reversi.get_players(game_id).unwrap(), [default_accounts.charlie, default_accounts.django]);
+            assert_eq!(reversi.get_active_player(game_id).unwrap(), default_accounts.charlie);
+            assert!(matches!(reversi.get_players(2), Err(ReversiError::GameNotFound)));
+            set_caller::<Environment>(default_accounts.django);
+            assert!(reversi.join_game(game_id).is_ok());
+            set_caller::<Environment>(default_accounts.charlie);
+
+            // Moves in one game do not affect the others.
+            assert!(reversi.make_move(game_id, 3, 2).is_ok());
+            assert_eq!(reversi.get_active_player(game_id).unwrap(), default_accounts.django);
+            assert_eq!(reversi.get_active_player(0).unwrap(), default_accounts.alice);
+            assert_eq!(reversi.get_board(0).unwrap(), Board::new(6).unwrap().to_grid());
+
+            // Charlie is not a player of game 0.
+            assert!(matches!(reversi.make_move(0, 2, 1), Err(ReversiError::InvalidPlayer)));
+            assert!(matches!(reversi.make_move(2, 2, 1), Err(ReversiError::GameNotFound)));
         }
     }
 
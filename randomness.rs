@@ -0,0 +1,50 @@
+//! Random source of the chain, reached through a chain extension.
+//! Only games that ask for randomness call it, so the contract also runs on chains without it.
+
+use ink::env::{
+    DefaultEnvironment,
+    Environment,
+};
+
+pub const FETCH_RANDOM: u32 = 1101;
+
+#[ink::chain_extension]
+pub trait Randomness {
+    type ErrorCode = RandomnessError;
+
+    // Random bytes seeded by `subject` and by the chain's current randomness.
+    #[ink(extension = 1101)]
+    fn fetch_random(subject: [u8; 32]) -> [u8; 32];
+}
+
+#[derive(Clone, Copy, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum RandomnessError {
+    FailGetRandomSource,
+}
+
+impl ink::env::chain_extension::FromStatusCode for RandomnessError {
+    fn from_status_code(status_code: u32) -> Result<(), Self> {
+        match status_code {
+            0 => Ok(()),
+            _ => Err(Self::FailGetRandomSource),
+        }
+    }
+}
+
+// Default environment with the randomness chain extension.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum ReversiEnvironment {}
+
+impl Environment for ReversiEnvironment {
+    const MAX_EVENT_TOPICS: usize = <DefaultEnvironment as Environment>::MAX_EVENT_TOPICS;
+
+    type AccountId = <DefaultEnvironment as Environment>::AccountId;
+    type Balance = <DefaultEnvironment as Environment>::Balance;
+    type Hash = <DefaultEnvironment as Environment>::Hash;
+    type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+    type Timestamp = <DefaultEnvironment as Environment>::Timestamp;
+
+    type ChainExtension = Randomness;
+}
@@ -0,0 +1,79 @@
+//! Elo ratings in integer maths, as contracts have no floating point numbers.
+//! Scores and expected scores are in per mille: a win is 1000, a draw 500 and a loss 0.
+
+pub type Rating = u32;
+
+pub const DEFAULT_RATING: Rating = 1500;
+// Maximum rating change of a single game.
+pub const K_FACTOR: u32 = 32;
+
+pub const WIN: u32 = 1000;
+pub const DRAW: u32 = 500;
+pub const LOSS: u32 = 0;
+
+// Rating difference between two entries of `EXPECTED_SCORES`.
+const STEP: u32 = 25;
+// Expected score of the stronger player, 1000 / (1 + 10^(-d / 400)), for d = 0, 25, ..., 800.
+// Beyond 800 points the difference is treated as 800.
+const EXPECTED_SCORES: [u32; 33] = [
+    500, 536, 571, 606, 640, 673, 703, 733, 760, 785, 808, 830, 849, 867, 882, 896, 909, 920, 930, 939, 947, 954,
+    960, 965, 969, 973, 977, 980, 983, 985, 987, 989, 990,
+];
+
+// Expected score of `rating` against `opponent`, interpolated between the table entries.
+pub fn expected_score(rating: Rating, opponent: Rating) -> u32 {
+    let difference = rating.abs_diff(opponent).min(STEP * (EXPECTED_SCORES.len() as u32 - 1));
+    let index = (difference / STEP) as usize;
+    let offset = difference % STEP;
+    let lower = EXPECTED_SCORES[index];
+    let upper = EXPECTED_SCORES.get(index + 1).copied().unwrap_or(lower);
+    let expected = lower + ((upper - lower) * offset + STEP / 2) / STEP;
+
+    if rating >= opponent {
+        expected
+    } else {
+        WIN - expected
+    }
+}
+
+// New ratings of both players after a game where the first one scored `score`.
+// The change is rounded once so that what one player gains the other loses.
+pub fn update(rating: Rating, opponent: Rating, score: u32) -> (Rating, Rating) {
+    let expected = expected_score(rating, opponent);
+    let gained = score >= expected;
+    let change = (K_FACTOR * score.abs_diff(expected) + WIN / 2) / WIN;
+
+    if gained {
+        (rating.saturating_add(change), opponent.saturating_sub(change))
+    } else {
+        (rating.saturating_sub(change), opponent.saturating_add(change))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expected_score_ok() {
+        assert_eq!(expected_score(1500, 1500), 500);
+        assert_eq!(expected_score(1700, 1500), 760);
+        assert_eq!(expected_score(1500, 1700), 240);
+        // Halfway between 100 (640) and 125 (673).
+        assert_eq!(expected_score(1612, 1500), 656);
+        assert_eq!(expected_score(2800, 1000), 990);
+        assert_eq!(expected_score(1000, 2800), 10);
+    }
+
+    #[test]
+    fn update_ok() {
+        assert_eq!(update(1500, 1500, WIN), (1516, 1484));
+        assert_eq!(update(1500, 1500, LOSS), (1484, 1516));
+        assert_eq!(update(1500, 1500, DRAW), (1500, 1500));
+        assert_eq!(update(1700, 1500, WIN), (1708, 1492));
+        assert_eq!(update(1700, 1500, LOSS), (1676, 1524));
+        assert_eq!(update(1700, 1500, DRAW), (1692, 1508));
+        assert_eq!(update(10, 2000, LOSS), (10, 2000));
+        assert_eq!(update(5, 1500, LOSS), (5, 1500));
+    }
+}
@@ -0,0 +1,540 @@
+//! Tournament pairings and standings, independent of the contract and of the ink! environment.
+//! Players are identified by their registration index.
+
+use ink::prelude::vec::Vec;
+
+use crate::engine::Disk;
+
+pub const MAX_PLAYERS: u32 = 64;
+// Most rounds of a Swiss tournament, as many as a full round robin of `MAX_PLAYERS`.
+pub const MAX_SWISS_ROUNDS: u32 = MAX_PLAYERS - 1;
+// Pairings tried for a Swiss round before settling for a greedy one, which bounds its cost.
+const PAIRING_BUDGET: u32 = 10_000;
+
+// Points are counted in halves: a win or a bye is worth 2 and a draw 1.
+pub const WIN_POINTS: u32 = 2;
+pub const DRAW_POINTS: u32 = 1;
+
+#[derive(Clone, Copy, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum Format {
+    // Everyone plays everyone once.
+    RoundRobin,
+    // Players with equal points meet, for a fixed number of rounds. It is cut down to
+    // the rounds of a round robin when fewer players register.
+    Swiss { rounds: u32 },
+    // Single elimination. Players are seeded in registration order.
+    Knockout { tiebreak: Tiebreak },
+}
+
+impl Format {
+    pub fn round_count(self, player_count: u32) -> u32 {
+        match self {
+            // An odd field gets a bye in every round.
+            Self::RoundRobin => player_count + player_count % 2 - 1,
+            Self::Swiss { rounds } => rounds.min(Self::RoundRobin.round_count(player_count)),
+            Self::Knockout { .. } => player_count.next_power_of_two().trailing_zeros(),
+        }
+    }
+}
+
+// How a drawn knockout game is decided.
+#[derive(Clone, Copy, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum Tiebreak {
+    // The players meet again with colours swapped, until one of them wins.
+    Rematch,
+    // The better seeded player goes through.
+    HigherSeed,
+}
+
+#[derive(Clone, Copy, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Pairing {
+    pub black: u32,
+    pub white: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Round {
+    pub pairings: Vec<Pairing>,
+    // Player left out of an odd field, who scores a win.
+    pub bye: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, scale::Decode, scale::Encode, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct Standing {
+    pub player: u32,
+    pub points: u32,
+    // Sum of the points of every opponent met.
+    pub buchholz: u32,
+    // Own disks minus the opponent's disks, over all games.
+    pub disk_differential: i32,
+}
+
+#[derive(Clone, Debug, Default, scale::Decode, scale::Encode, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+struct Record {
+    points: u32,
+    disk_differential: i32,
+    opponents: Vec<u32>,
+    // Games played with Black minus games played with White.
+    colour_balance: i32,
+    had_bye: bool,
+}
+
+// Results of every player of a tournament, indexed by player.
+#[derive(Clone, Debug, Default, scale::Decode, scale::Encode, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Table {
+    records: Vec<Record>,
+}
+
+impl Table {
+    pub fn new(player_count: u32) -> Self {
+        Self {
+            records: (0..player_count).map(|_| Record::default()).collect(),
+        }
+    }
+
+    // Records a finished game. `score` is (black_count, white_count) as returned by `count_disks`.
+    pub fn record_game(&mut self, pairing: Pairing, score: (u8, u8), winner: Option<Disk>) {
+        let (black_count, white_count) = score;
+        let (black_points, white_points) = match winner {
+            Some(Disk::Black) => (WIN_POINTS, 0),
+            Some(Disk::White) => (0, WIN_POINTS),
+            None => (DRAW_POINTS, DRAW_POINTS),
+        };
+        let differential = i32::from(black_count) - i32::from(white_count);
+
+        let black = &mut self.records[pairing.black as usize];
+        black.points += black_points;
+        black.disk_differential += differential;
+        black.opponents.push(pairing.white);
+        black.colour_balance += 1;
+
+        let white = &mut self.records[pairing.white as usize];
+        white.points += white_points;
+        white.disk_differential -= differential;
+        white.opponents.push(pairing.black);
+        white.colour_balance -= 1;
+    }
+
+    pub fn record_bye(&mut self, player: u32) {
+        let record = &mut self.records[player as usize];
+        record.points += WIN_POINTS;
+        record.had_bye = true;
+    }
+
+    // Players from first to last, by points, then Buchholz, then disk differential.
+    // Players still tied keep their registration order.
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings = self
+            .records
+            .iter()
+            .enumerate()
+            .map(|(player, record)| Standing {
+                player: player as u32,
+                points: record.points,
+                buchholz: record
+                    .opponents
+                    .iter()
+                    .map(|&opponent| self.records[opponent as usize].points)
+                    .sum(),
+                disk_differential: record.disk_differential,
+            })
+            .collect::<Vec<_>>();
+
+        standings.sort_by(|a, b| {
+            b.points
+                .cmp(&a.points)
+                .then(b.buchholz.cmp(&a.buchholz))
+                .then(b.disk_differential.cmp(&a.disk_differential))
+        });
+        standings
+    }
+
+    // Pairs players that are next to each other in the standings and have not met yet.
+    // When that is not possible, the pairing with the fewest rematches is chosen.
+    pub fn swiss_round(&self) -> Round {
+        let mut players = self.standings().iter().map(|standing| standing.player).collect::<Vec<_>>();
+
+        // The lowest placed player who has not had a bye yet sits out an odd field.
+        let bye = (players.len() % 2 == 1).then(|| {
+            let index = players
+                .iter()
+                .rposition(|&player| !self.records[player as usize].had_bye)
+                .unwrap_or(players.len() - 1);
+            players.remove(index)
+        });
+
+        // Searches with no rematch, then one, and so on, until the budget runs out.
+        let mut budget = PAIRING_BUDGET;
+        let pairs = (0..=players.len() as u32 / 2)
+            .find_map(|rematches| self.pair(&players, rematches, &mut budget))
+            .unwrap_or_else(|| self.pair_greedily(&players));
+        Round {
+            pairings: pairs.into_iter().map(|(a, b)| self.assign_colours(a, b)).collect(),
+            bye,
+        }
+    }
+
+    // Pairs `players` with at most `rematches` pairs who have already met, each with the
+    // closest opponent possible. `None` if there is no such pairing or the budget runs out.
+    fn pair(&self, players: &[u32], rematches: u32, budget: &mut u32) -> Option<Vec<(u32, u32)>> {
+        let Some((&first, rest)) = players.split_first() else {
+            return Some(Vec::new())
+        };
+
+        for (index, &opponent) in rest.iter().enumerate() {
+            *budget = budget.checked_sub(1)?;
+            let is_rematch = self.has_met(first, opponent);
+            if is_rematch && rematches == 0 {
+                continue
+            }
+            let mut remaining = rest.to_vec();
+            remaining.remove(index);
+            if let Some(mut pairs) = self.pair(&remaining, rematches - u32::from(is_rematch), budget) {
+                pairs.insert(0, (first, opponent));
+                return Some(pairs)
+            }
+        }
+        None
+    }
+
+    // Pairs each player with the closest one left that it has not met, or else the closest one.
+    fn pair_greedily(&self, players: &[u32]) -> Vec<(u32, u32)> {
+        let mut remaining = players.to_vec();
+        let mut pairs = Vec::new();
+        while remaining.len() >= 2 {
+            let first = remaining.remove(0);
+            let index = remaining
+                .iter()
+                .position(|&opponent| !self.has_met(first, opponent))
+                .unwrap_or(0);
+            pairs.push((first, remaining.remove(index)));
+        }
+        pairs
+    }
+
+    fn has_met(&self, a: u32, b: u32) -> bool {
+        self.records[a as usize].opponents.contains(&b)
+    }
+
+    // The player who had Black less often gets Black, the higher placed one on a tie.
+    fn assign_colours(&self, a: u32, b: u32) -> Pairing {
+        if self.records[a as usize].colour_balance <= self.records[b as usize].colour_balance {
+            Pairing { black: a, white: b }
+        } else {
+            Pairing { black: b, white: a }
+        }
+    }
+}
+
+// Single elimination bracket. Seeds are player indices, 0 being the top seed.
+#[derive(Clone, Debug, Default, scale::Decode, scale::Encode, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Bracket {
+    // Players of the current round in bracket order, two seats per game.
+    // An empty seat gives a bye to the player next to it.
+    seats: Vec<Option<u32>>,
+    // Players knocked out, with the round they lost in, counted from 0.
+    knocked_out: Vec<(u32, u32)>,
+    round: u32,
+}
+
+impl Bracket {
+    // Top seeds are spread so that they can only meet in the last rounds,
+    // and get the byes when the field is not a power of two.
+    pub fn new(player_count: u32) -> Self {
+        let mut order = Vec::from([0]);
+        while (order.len() as u32) < player_count {
+            let size = order.len() as u32 * 2;
+            order = order.iter().flat_map(|&seed| [seed, size - 1 - seed]).collect();
+        }
+
+        Self {
+            seats: order
+                .into_iter()
+                .map(|seed| (seed < player_count).then_some(seed))
+                .collect(),
+            knocked_out: Vec::new(),
+            round: 0,
+        }
+    }
+
+    // Games of the current round. The better seed plays Black.
+    pub fn pairings(&self) -> Vec<Pairing> {
+        self.seats
+            .chunks(2)
+            .filter_map(|seats| match *seats {
+                [Some(a), Some(b)] => Some(Pairing { black: a.min(b), white: a.max(b) }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn knock_out(&mut self, player: u32) {
+        if let Some(seat) = self.seats.iter_mut().find(|seat| **seat == Some(player)) {
+            *seat = None;
+            self.knocked_out.push((self.round, player));
+        }
+    }
+
+    // Moves the players left in each game to the next round.
+    pub fn advance(&mut self) {
+        self.seats = self.seats.chunks(2).map(|seats| seats[0].or(seats[1])).collect();
+        self.round += 1;
+    }
+
+    // Players grouped by finishing place, from the winner down.
+    // Players knocked out in the same round share their place.
+    pub fn places(&self) -> Vec<Vec<u32>> {
+        let mut places = Vec::from([self.seats.iter().flatten().copied().collect::<Vec<_>>()]);
+        for round in (0..self.round + 1).rev() {
+            let players = self
+                .knocked_out
+                .iter()
+                .filter(|(knocked_out_in, _)| *knocked_out_in == round)
+                .map(|(_, player)| *player)
+                .collect::<Vec<_>>();
+            if !players.is_empty() {
+                places.push(players);
+            }
+        }
+        places
+    }
+}
+
+// Splits `pool` by `shares`, given in percent from first place down. Players sharing a place
+// split the shares of the places they cover. Rounding leftovers and unused shares go to the winner.
+pub fn split_prize(pool: u128, shares: &[u32], places: &[Vec<u32>]) -> Vec<(u32, u128)> {
+    let mut prizes = Vec::new();
+    let mut place = 0;
+    for players in places {
+        let covered = shares.iter().skip(place).take(players.len());
+        let share = pool * covered.map(|&share| u128::from(share)).sum::<u128>() / 100;
+        for &player in players {
+            prizes.push((player, share / players.len() as u128));
+        }
+        place += players.len();
+    }
+
+    let paid = prizes.iter().map(|(_, prize)| prize).sum::<u128>();
+    if let Some((_, prize)) = prizes.first_mut() {
+        *prize += pool - paid;
+    }
+    prizes
+}
+
+// Pairings of a round robin round, counted from 0, with the circle method.
+// Player 0 stays in place while the others rotate by one seat every round.
+pub fn round_robin_round(player_count: u32, round: u32) -> Round {
+    // An odd field gets a dummy player, and whoever meets it has a bye.
+    let seats = player_count + player_count % 2;
+    let player_at = |seat: u32| {
+        if seat == 0 {
+            0
+        } else {
+            1 + (seat - 1 + round) % (seats - 1)
+        }
+    };
+
+    let mut result = Round::default();
+    for seat in 0..seats / 2 {
+        let (a, b) = (player_at(seat), player_at(seats - 1 - seat));
+        if b >= player_count {
+            result.bye = Some(a);
+        } else if a >= player_count {
+            result.bye = Some(b);
+        } else if (seat + round).is_multiple_of(2) {
+            result.pairings.push(Pairing { black: a, white: b });
+        } else {
+            result.pairings.push(Pairing { black: b, white: a });
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opponents(pairings: &[Pairing]) -> Vec<(u32, u32)> {
+        pairings
+            .iter()
+            .map(|pairing| (pairing.black.min(pairing.white), pairing.black.max(pairing.white)))
+            .collect()
+    }
+
+    #[test]
+    fn round_robin_ok() {
+        for player_count in [2, 3, 4, 5, 8] {
+            let rounds = Format::RoundRobin.round_count(player_count);
+            let mut games = Vec::new();
+            let mut byes = Vec::new();
+            for round in 0..rounds {
+                let round = round_robin_round(player_count, round);
+                games.extend(opponents(&round.pairings));
+                byes.extend(round.bye);
+            }
+
+            games.sort();
+            let expected = (0..player_count)
+                .flat_map(|a| (a + 1..player_count).map(move |b| (a, b)))
+                .collect::<Vec<_>>();
+            assert_eq!(games, expected);
+            if player_count % 2 == 1 {
+                byes.sort();
+                assert_eq!(byes, (0..player_count).collect::<Vec<_>>());
+            } else {
+                assert!(byes.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn standings_ok() {
+        let mut table = Table::new(4);
+        table.record_game(Pairing { black: 0, white: 1 }, (20, 16), Some(Disk::Black));
+        table.record_game(Pairing { black: 2, white: 3 }, (18, 18), None);
+        table.record_game(Pairing { black: 3, white: 0 }, (30, 6), Some(Disk::Black));
+        table.record_game(Pairing { black: 1, white: 2 }, (10, 26), Some(Disk::White));
+
+        // 2 and 3 are tied on points, 3 met stronger opponents.
+        let standings = table.standings();
+        assert_eq!(
+            standings,
+            vec![
+                Standing { player: 3, points: 3, buchholz: 5, disk_differential: 24 },
+                Standing { player: 2, points: 3, buchholz: 3, disk_differential: 16 },
+                Standing { player: 0, points: 2, buchholz: 3, disk_differential: -20 },
+                Standing { player: 1, points: 0, buchholz: 5, disk_differential: -20 },
+            ]
+        );
+    }
+
+    #[test]
+    fn swiss_round_ok() {
+        let mut table = Table::new(5);
+        let round = table.swiss_round();
+        assert_eq!(round.pairings, vec![Pairing { black: 0, white: 1 }, Pairing { black: 2, white: 3 }]);
+        assert_eq!(round.bye, Some(4));
+
+        table.record_game(Pairing { black: 0, white: 1 }, (20, 16), Some(Disk::Black));
+        table.record_game(Pairing { black: 2, white: 3 }, (16, 20), Some(Disk::White));
+        table.record_bye(4);
+
+        // 0, 3 and 4 lead with a win each, and 2 placed last sits out.
+        // Players who had White get Black.
+        let round = table.swiss_round();
+        assert_eq!(round.pairings, vec![Pairing { black: 3, white: 0 }, Pairing { black: 1, white: 4 }]);
+        assert_eq!(round.bye, Some(2));
+    }
+
+    #[test]
+    fn bracket_ok() {
+        let bracket = Bracket::new(8);
+        assert_eq!(
+            opponents(&bracket.pairings()),
+            vec![(0, 7), (3, 4), (1, 6), (2, 5)]
+        );
+
+        // The top three seeds skip the first round.
+        let mut bracket = Bracket::new(5);
+        assert_eq!(Format::Knockout { tiebreak: Tiebreak::Rematch }.round_count(5), 3);
+        assert_eq!(bracket.pairings(), vec![Pairing { black: 3, white: 4 }]);
+        bracket.knock_out(3);
+        bracket.advance();
+        assert_eq!(bracket.pairings(), vec![Pairing { black: 0, white: 4 }, Pairing { black: 1, white: 2 }]);
+        bracket.knock_out(4);
+        bracket.knock_out(1);
+        bracket.advance();
+        assert_eq!(bracket.pairings(), vec![Pairing { black: 0, white: 2 }]);
+        bracket.knock_out(0);
+        bracket.advance();
+        assert!(bracket.pairings().is_empty());
+        assert_eq!(bracket.places(), vec![vec![2], vec![0], vec![4, 1], vec![3]]);
+    }
+
+    #[test]
+    fn split_prize_ok() {
+        let places = vec![vec![2], vec![0], vec![4, 1], vec![3]];
+        assert_eq!(
+            split_prize(1_000, &[50, 30, 20], &places),
+            vec![(2, 500), (0, 300), (4, 100), (1, 100), (3, 0)]
+        );
+        // Fourth place is shared with third, and the rounding leftover goes to the winner.
+        assert_eq!(
+            split_prize(1_001, &[50, 25, 15, 10], &places),
+            vec![(2, 501), (0, 250), (4, 125), (1, 125), (3, 0)]
+        );
+        assert_eq!(split_prize(1_000, &[60, 40], &[vec![0, 1]]), vec![(0, 500), (1, 500)]);
+    }
+
+    #[test]
+    fn swiss_round_avoids_rematches() {
+        let mut table = Table::new(4);
+        table.record_game(Pairing { black: 0, white: 1 }, (20, 16), Some(Disk::Black));
+        table.record_game(Pairing { black: 2, white: 3 }, (20, 16), Some(Disk::Black));
+        table.record_game(Pairing { black: 2, white: 0 }, (20, 16), Some(Disk::Black));
+        table.record_game(Pairing { black: 3, white: 1 }, (20, 16), Some(Disk::Black));
+
+        // 2 has already met 0 and 3, which leaves 1.
+        let round = table.swiss_round();
+        assert_eq!(opponents(&round.pairings), vec![(1, 2), (0, 3)]);
+
+        // With two players only, they meet again.
+        let mut table = Table::new(2);
+        table.record_game(Pairing { black: 0, white: 1 }, (20, 16), Some(Disk::Black));
+        assert_eq!(table.swiss_round().pairings, vec![Pairing { black: 1, white: 0 }]);
+    }
+
+    #[test]
+    fn swiss_round_minimises_rematches() {
+        assert_eq!(Format::Swiss { rounds: 9 }.round_count(8), 7);
+        assert_eq!(Format::Swiss { rounds: 9 }.round_count(5), 5);
+        assert_eq!(Format::Swiss { rounds: 3 }.round_count(8), 3);
+
+        // 0 has met everyone, so one rematch cannot be avoided, but the others can.
+        let mut table = Table::new(6);
+        for (black, white) in [(0, 1), (0, 2), (0, 3), (0, 4), (0, 5), (2, 3), (4, 5)] {
+            table.record_game(Pairing { black, white }, (18, 18), None);
+        }
+        let round = table.swiss_round();
+        let rematches = |round: &Round| round.pairings.iter().filter(|p| table.has_met(p.black, p.white)).count();
+        assert_eq!(round.pairings.len(), 3);
+        assert_eq!(rematches(&round), 1);
+
+        // Everyone has met everyone: the search gives up and every player still gets a game.
+        let mut table = Table::new(MAX_PLAYERS);
+        for black in 0..MAX_PLAYERS {
+            for white in black + 1..MAX_PLAYERS {
+                table.record_game(Pairing { black, white }, (18, 18), None);
+            }
+        }
+        let round = table.swiss_round();
+        let mut players = round.pairings.iter().flat_map(|p| [p.black, p.white]).collect::<Vec<_>>();
+        players.sort();
+        assert_eq!(players, (0..MAX_PLAYERS).collect::<Vec<_>>());
+    }
+}